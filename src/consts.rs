@@ -23,7 +23,7 @@ pub(crate) const ZERO_MESSAGE_BYTES_256: [u8; 16] =
 // The TLS version converts the 12-byte IV into 16 bytes by padding with 0001.
 pub(crate) const IV_BYTES: [u8; 16] = [
     0x31, 0x31, 0x31, 0x31, 0x31, 0x31, 0x31, 0x31, 0x31, 0x31, 0x31, 0x31, 0x0, 0x0, 0x0,
-    0x01, // GCM fills it out like this (when the IV is 12 bytes)
+    0x01, // GCM fills it out like this (when the IV is 12 bytes, else J0 is GHASH'd, see `gcm_j0`)
 ];
 pub(crate) const IV_BYTES_256: [u8; 16] = [
     0x3, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0,
@@ -39,3 +39,8 @@ pub(crate) const IV_BYTES_SHORT_256: [u8; 12] =
 
 pub(crate) const SIV_AAD: [u8; 16] =
     [0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0];
+
+// The `AESGCM(l)` circuit takes a fixed 16 byte AAD. Unlike `SIV_AAD` it is not zero, as the
+// circuit transposes the AAD block before hashing it, which a zero or symmetric AAD would hide.
+pub(crate) const GCM_AAD: [u8; 16] =
+    [0x0, 0x1, 0x2, 0x3, 0x4, 0x5, 0x6, 0x7, 0x8, 0x9, 0xa, 0xb, 0xc, 0xd, 0xe, 0xf];
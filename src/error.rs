@@ -0,0 +1,73 @@
+//! Errors returned by witness generation and proving
+
+use std::array::TryFromSliceError;
+
+use ark_relations::r1cs::SynthesisError;
+use ark_serialize::SerializationError;
+use thiserror::Error;
+
+use crate::witness::CipherMode;
+
+#[derive(Debug, Error)]
+pub enum Error {
+    /// a key, IV, AAD or plaintext the cipher or the target circuit does not take
+    #[error("invalid input: {0}")]
+    Input(String),
+    /// the authentication tag did not verify under the given key, nonce and AAD
+    #[error("{0:?} authentication tag mismatch")]
+    TagMismatch(CipherMode),
+    #[error("{0:?} encryption failed")]
+    Encryption(CipherMode),
+    /// the wasm or r1cs could not be read
+    #[error("failed to load circuit artifact {path}: {reason}")]
+    Artifact { path: String, reason: String },
+    /// the witness calculator rejected the inputs
+    #[error("witness computation failed: {0}")]
+    Witness(String),
+    #[error("circuit constraints are not satisfied")]
+    Unsatisfied,
+    #[error("proof did not verify")]
+    Verification,
+    /// a proving or verifying key set up for another circuit
+    #[error("key does not match the circuit: {0}")]
+    KeyMismatch(String),
+    /// a circuit output differs from what the native implementation computes
+    #[error("{signal} mismatch: expected {expected:?}, got {actual:?}")]
+    OutputMismatch { signal: String, expected: Vec<u8>, actual: Vec<u8> },
+    /// a public signal is out of range for its encoding
+    #[error("results should be {0}")]
+    InvalidSignal(&'static str),
+    #[error(transparent)]
+    Synthesis(#[from] SynthesisError),
+    #[error(transparent)]
+    Serialization(#[from] SerializationError),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+}
+
+impl From<TryFromSliceError> for Error {
+    fn from(e: TryFromSliceError) -> Self { Error::Input(e.to_string()) }
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Return [`Error::Input`] with a formatted message
+macro_rules! bail {
+    ($($arg:tt)+) => {
+        return Err($crate::error::Error::Input(format!($($arg)+)))
+    };
+}
+
+/// Return [`Error::Input`] with a formatted message unless the condition holds
+macro_rules! ensure {
+    ($cond:expr, $($arg:tt)+) => {
+        if !$cond {
+            $crate::error::bail!($($arg)+);
+        }
+    };
+}
+
+pub(crate) use bail;
+pub(crate) use ensure;
@@ -0,0 +1,163 @@
+//! Generate witnesses for folding AES-GCTR one block at a time with `AESGCTRFOLD(INPUT_LEN)`
+//!
+//! The fold accumulator `step_in[INPUT_LEN*2+4]` is laid out as
+//! - `step_in[0..INPUT_LEN]`: plaintext blocks folded so far
+//! - `step_in[INPUT_LEN..INPUT_LEN*2]`: ciphertext blocks folded so far
+//! - `step_in[INPUT_LEN*2..INPUT_LEN*2+4]`: big-endian counter of the last folded block
+
+use aes::{
+    cipher::{BlockEncrypt, KeyInit},
+    Aes128,
+};
+use serde::Serialize;
+
+use crate::{
+    error::{ensure, Result},
+    witness::{CipherMode, WitnessBuilder},
+    Block,
+};
+
+/// Private inputs to a single fold step
+#[derive(Debug, Clone, Serialize)]
+pub struct GctrFoldInputs {
+    pub key:       Vec<u8>,
+    pub iv:        Vec<u8>,
+    pub aad:       Vec<u8>,
+    pub plainText: Vec<u8>,
+}
+
+/// Everything needed to fold a whole plaintext through `AESGCTRFOLD(INPUT_LEN)`
+#[derive(Debug, Serialize)]
+pub struct GctrFoldWitness {
+    /// initial accumulator: zeroed plaintext and ciphertext, counter J0 = 1
+    pub z0:             Vec<u8>,
+    /// private inputs for each fold, in order
+    pub private_inputs: Vec<GctrFoldInputs>,
+    /// expected `step_out` of each fold, the `step_in` of the next
+    pub step_outs:      Vec<Vec<u8>>,
+}
+
+/// Fold sequence for encrypting `pt` with AES-128-GCTR, `INPUT_LEN` is `pt.len()`.
+pub fn gctr_fold_witnesses(
+    key: &[u8],
+    iv: &[u8],
+    aad: &[u8],
+    pt: &[u8],
+) -> Result<GctrFoldWitness> {
+    let input_len = pt.len();
+    ensure!(input_len > 0 && input_len.is_multiple_of(16), "INPUT_LEN must be a multiple of 16");
+    // `AESGCTRFOLD` takes the IV as is, it does not derive J0 from other lengths
+    ensure!(iv.len() == 12, "iv must be 12 bytes");
+    ensure!(aad.len() == 16, "aad must be 16 bytes");
+    // `AESGCTRFOLDABLE` increments the last row of the column-major counter block, bytes 3, 7, 11
+    // and 15, so a carry out of the counter's low byte lands in the IV.
+    ensure!(input_len / 16 < 0xff, "AESGCTRFOLD folds at most 254 blocks");
+
+    // CTR and GCTR share a keystream, starting at inc32(J0)
+    let witness = WitnessBuilder::new(CipherMode::Ctr128).key(key).iv(iv).pt(pt).build()?;
+
+    let mut z0 = vec![0; input_len * 2 + 4];
+    z0[input_len * 2..].copy_from_slice(&1u32.to_be_bytes());
+
+    let mut step_in = z0.clone();
+    let mut private_inputs = vec![];
+    let mut step_outs = vec![];
+    for (i, (pt_block, ct_block)) in pt.chunks(16).zip(witness.ct.chunks(16)).enumerate() {
+        let mut step_out = step_in;
+        step_out[i * 16..][..16].copy_from_slice(pt_block);
+        step_out[input_len + i * 16..][..16].copy_from_slice(ct_block);
+        step_out[input_len * 2..].copy_from_slice(&(i as u32 + 2).to_be_bytes());
+
+        private_inputs.push(GctrFoldInputs {
+            key:       key.to_vec(),
+            iv:        iv.to_vec(),
+            aad:       aad.to_vec(),
+            plainText: pt_block.to_vec(),
+        });
+        step_outs.push(step_out.clone());
+        step_in = step_out;
+    }
+
+    Ok(GctrFoldWitness { z0, private_inputs, step_outs })
+}
+
+/// A fold accumulator split into its parts
+#[derive(Debug, PartialEq, Eq)]
+pub struct GctrFoldState {
+    pub plaintext:  Vec<u8>,
+    pub ciphertext: Vec<u8>,
+    pub counter:    u32,
+}
+
+/// Split a `step_in` or `step_out` accumulator into plaintext, ciphertext and counter.
+pub fn decode_fold_state(step: &[u8]) -> Result<GctrFoldState> {
+    let input_len = fold_input_len(step)?;
+    Ok(GctrFoldState {
+        plaintext:  step[..input_len].to_vec(),
+        ciphertext: step[input_len..input_len * 2].to_vec(),
+        counter:    u32::from_be_bytes(step[input_len * 2..].try_into()?),
+    })
+}
+
+/// Native model of one `AESGCTRFOLD(INPUT_LEN)` step, predicts `step_out` from `step_in`.
+///
+/// This follows the circuit signal for signal rather than the happy path, so it also predicts the
+/// circuit's output for accumulators a correct fold sequence never produces, e.g. a zero counter.
+pub fn gctr_fold_step(step_in: &[u8], inputs: &GctrFoldInputs) -> Result<Vec<u8>> {
+    let input_len = fold_input_len(step_in)?;
+    ensure!(inputs.key.len() == 16, "key must be 16 bytes");
+    ensure!(inputs.iv.len() == 12, "iv must be 12 bytes");
+    ensure!(inputs.plainText.len() == 16, "plainText must be 16 bytes");
+
+    // BytesToBits(4) then Bits2Num(32) over the reversed bits: a big-endian counter, minus one.
+    // Field arithmetic, a zero counter gives p - 1, so keep the sign.
+    let last_counter = &step_in[input_len * 2..];
+    let counter = u32::from_be_bytes(last_counter.try_into()?) as i64 - 1;
+
+    // AESGCTRFOLDABLE: the counter block is iv || lastCounter, and `IncrementWord` increments its
+    // last row, which is bytes 3, 7, 11 and 15 of the column-major block
+    let mut counter_block = Block::default();
+    counter_block[..12].copy_from_slice(&inputs.iv);
+    counter_block[12..].copy_from_slice(last_counter);
+    let row = [counter_block[3], counter_block[7], counter_block[11], counter_block[15]];
+    let row = u32::from_be_bytes(row).wrapping_add(1).to_be_bytes();
+    [3, 7, 11, 15].iter().zip(row).for_each(|(&i, byte)| counter_block[i] = byte);
+
+    // GCTR(16) over a single block
+    let mut keystream = counter_block;
+    Aes128::new(Block::from_slice(&inputs.key)).encrypt_block(&mut keystream);
+    let ct: Vec<u8> = inputs.plainText.iter().zip(keystream).map(|(p, k)| p ^ k).collect();
+
+    // accumulate plaintext, then ciphertext, then the counter, which the circuit reads off the
+    // last column of the counter block, bytes 12 to 15
+    let step_out = write_to_index(step_in, &inputs.plainText, counter * 16);
+    let step_out = write_to_index(&step_out, &ct, input_len as i64 + counter * 16);
+    Ok(write_to_index(&step_out, &counter_block[12..], input_len as i64 * 2))
+}
+
+/// `INPUT_LEN` of an accumulator of `INPUT_LEN*2+4` bytes
+fn fold_input_len(step: &[u8]) -> Result<usize> {
+    ensure!(
+        step.len() >= 4 && (step.len() - 4).is_multiple_of(32),
+        "step must be INPUT_LEN*2+4 bytes"
+    );
+    Ok((step.len() - 4) / 2)
+}
+
+/// Native model of `WriteToIndex(m, n)`, with `index` standing in for a small field element.
+///
+/// The circuit marks `index` and `index + n`, writes wherever exactly one mark has been passed,
+/// and reads `data` from a running offset, zero once past `n`.
+fn write_to_index(array: &[u8], data: &[u8], index: i64) -> Vec<u8> {
+    let end = index + data.len() as i64;
+    let mut out = array.to_vec();
+    let (mut accum, mut offset) = (0, 0);
+    for (i, byte) in out.iter_mut().enumerate() {
+        accum += (i as i64 == index) as u8 + (i as i64 == end) as u8;
+        if accum == 1 {
+            *byte = data.get(offset).copied().unwrap_or(0);
+            offset += 1;
+        }
+    }
+    out
+}
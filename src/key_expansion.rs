@@ -0,0 +1,53 @@
+//! Native AES key expansion, see FIPS-197 section 5.2 and `aes/key_expansion.circom`
+
+use crate::error::{ensure, Result};
+
+/// round constants, `RCon(round)` in the circuit
+const RCON: [u8; 10] = [0x01, 0x02, 0x04, 0x08, 0x10, 0x20, 0x40, 0x80, 0x1b, 0x36];
+
+/// multiply in GF(2^8) modulo x^8 + x^4 + x^3 + x + 1
+pub(crate) fn gmul(mut a: u8, mut b: u8) -> u8 {
+    let mut product = 0;
+    while b != 0 {
+        if b & 1 == 1 {
+            product ^= a;
+        }
+        let carry = a & 0x80;
+        a <<= 1;
+        if carry != 0 {
+            a ^= 0x1b;
+        }
+        b >>= 1;
+    }
+    product
+}
+
+/// S-box computed the way `SBox128()` does it: inverse in GF(2^8), then the affine transform
+pub(crate) fn sbox(byte: u8) -> u8 {
+    let inv = (1..=255).find(|&x| gmul(byte, x) == 1).unwrap_or(0);
+    inv ^ inv.rotate_left(1) ^ inv.rotate_left(2) ^ inv.rotate_left(3) ^ inv.rotate_left(4) ^ 0x63
+}
+
+/// Expand a 16, 24 or 32 byte key into its `4 * (Nr + 1)` word key schedule
+pub(crate) fn expand_key(key: &[u8]) -> Result<Vec<[u8; 4]>> {
+    ensure!(matches!(key.len(), 16 | 24 | 32), "AES key must be 16, 24 or 32 bytes");
+    let nk = key.len() / 4;
+    let nr = nk + 6;
+
+    let mut words: Vec<[u8; 4]> = key.chunks(4).map(|word| word.try_into().unwrap()).collect();
+    for i in nk..4 * (nr + 1) {
+        let mut temp = words[i - 1];
+        if i % nk == 0 {
+            // RotWord, SubWord, Rcon
+            temp.rotate_left(1);
+            temp = temp.map(sbox);
+            temp[0] ^= RCON[i / nk - 1];
+        } else if nk > 6 && i % nk == 4 {
+            temp = temp.map(sbox);
+        }
+        let prev = words[i - nk];
+        words.push(std::array::from_fn(|j| prev[j] ^ temp[j]));
+    }
+
+    Ok(words)
+}
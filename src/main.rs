@@ -4,14 +4,19 @@
 #![allow(non_snake_case)]
 #![allow(clippy::clone_on_copy)]
 
-use std::io;
-
 use aes::{cipher::generic_array::GenericArray, Aes256};
 use cipher::consts::U16;
 use utils::make_json_witness;
 
 mod consts;
+mod error;
+mod fold;
+mod key_expansion;
 mod proof;
+mod snarkjs;
+mod solidity;
+mod tls;
+mod trace;
 mod utils;
 mod witness;
 
@@ -22,7 +27,14 @@ const SIV_R1CS: &str = "./build/gcm_siv_dec_2_keys_test.r1cs";
 const AES_256_CRT_WTNS: &str = "./build/aes_256_ctr_test_js/aes_256_ctr_test.wasm";
 const AES_256_CRT_R1CS: &str = "./build/aes_256_ctr_test.r1cs";
 
+/// Groth16 keys, written once by `CircuitDescriptor::setup`
+const SIV_PK: &str = "./build/gcm_siv_dec_2_keys_test.pk";
+const SIV_VK: &str = "./build/gcm_siv_dec_2_keys_test.vk";
+const AES_256_CRT_PK: &str = "./build/aes_256_ctr_test.pk";
+const AES_256_CRT_VK: &str = "./build/aes_256_ctr_test.vk";
+
 pub type AAD = [u8; 5];
+pub type Tls12AAD = [u8; 13];
 pub type Nonce = [u8; 12];
 
 // convenience type aliases for AES-CTR, wrapping type aliases from `ctr` crate
@@ -33,12 +45,41 @@ pub(crate) type Aes128Ctr32BE = ctr::Ctr32BE<aes::Aes128>; // Note: Ctr32BE is u
 /// AES 128-bit block
 pub(crate) type Block = GenericArray<u8, U16>;
 
+/// AES 128-bit block as the circom templates hold it, `[row][column]`
+pub(crate) type State = [[u8; 4]; 4];
+
 #[tokio::main]
-async fn main() -> io::Result<()> {
-    let mut witness = witness::aes_witnesses(witness::CipherMode::Vanilla).unwrap();
-    witness.iv.extend_from_slice(&[0, 0, 0, 0]);
+async fn main() -> error::Result<()> {
+    std::fs::create_dir_all("inputs")?;
+
+    // AESGCM(l) circuit inputs and expected outputs
+    let witness = witness::aes_witnesses(witness::CipherMode::GCM128)?;
+    make_json_witness(&witness, witness::CipherMode::GCM128)?;
+
+    // GHASH(NUM_BLOCKS) as run inside AESGCM(l)
+    let ghash = witness::aes_gcm_ghash_witness(&witness)?;
+    utils::make_ghash_json_witness(&ghash)?;
+
+    // AESGCTRFOLD(INPUT_LEN) fold sequence over the same plaintext
+    let fold = fold::gctr_fold_witnesses(&witness.key, &witness.iv, &witness.aad, &witness.pt)?;
+    utils::make_fold_json_witness(&fold)?;
+
+    // AESGCM over the 5 byte AAD of a TLS 1.3 record
+    let mut session =
+        tls::Tls13Session::new(consts::KEY_ASCII.as_bytes(), consts::IV_ASCII.as_bytes())?;
+    let record = session.seal(consts::MESSAGE.as_bytes(), tls::APPLICATION_DATA, 0)?;
+    utils::make_aes_gcm_aad_json_witness(&record.witness)?;
 
-    make_json_witness(&witness, witness::CipherMode::Vanilla).unwrap();
+    let witness = witness::aes_witnesses(witness::CipherMode::Vanilla)?;
+    make_json_witness(&witness, witness::CipherMode::Vanilla)?;
+
+    // KeyExpansion() and Cipher() over the same key and block
+    let key_expansion = witness::key_expansion_witness(&witness.key)?;
+    utils::make_key_expansion_json_witness(&key_expansion)?;
+    let cipher = witness::cipher_witness(witness.key[..].try_into()?, witness.pt[..].try_into()?);
+    utils::make_cipher_json_witness(&cipher)?;
+    let trace = trace::cipher_trace(&witness.key, witness.pt[..].try_into()?)?;
+    utils::make_cipher_trace_json(&trace)?;
 
     Ok(())
 }
@@ -47,6 +88,8 @@ async fn main() -> io::Result<()> {
 mod tests {
     use super::*;
 
+    type GrothBn = ark_groth16::Groth16<ark_bn254::Bn254, ark_circom::CircomReduction>;
+
     // Test the AES-GCM-SIV circuit (from electron labs)
     #[tokio::test]
     async fn test_aes_gcm_siv() {
@@ -62,8 +105,826 @@ mod tests {
         // tls1.3 junk
         witness.iv.extend_from_slice(&[0; 4]);
 
+        // set up the keys once
+        let descriptor = proof::CircuitDescriptor::aes_gcm_siv(
+            SIV_WTNS,
+            SIV_R1CS,
+            witness.pt.len(),
+            witness.aad.len(),
+        );
+        if !std::path::Path::new(SIV_PK).exists() {
+            descriptor.setup(SIV_PK, SIV_VK).unwrap();
+        }
+
         // generate proof
-        proof::gen_proof_aes_gcm_siv(&witness, SIV_WTNS, SIV_R1CS);
+        let proof = proof::gen_proof_aes_gcm_siv(&witness, SIV_WTNS, SIV_R1CS, SIV_PK).unwrap();
+
+        // verify it again with the persisted verifying key
+        let vk = descriptor.verifying_key(SIV_VK).unwrap();
+        descriptor.verify(&vk, &proof.proof, &proof.public_inputs).unwrap();
+    }
+
+    // Test the AES-256-CTR circuit (from electron labs)
+    #[tokio::test]
+    async fn test_aes_256_ctr() {
+        // generate witness
+        let witness = witness::aes_witnesses(witness::CipherMode::Ctr256).unwrap();
+
+        // set up the keys once
+        let descriptor = proof::CircuitDescriptor::aes_256_ctr(
+            AES_256_CRT_WTNS,
+            AES_256_CRT_R1CS,
+            witness.pt.len(),
+        );
+        if !std::path::Path::new(AES_256_CRT_PK).exists() {
+            descriptor.setup(AES_256_CRT_PK, AES_256_CRT_VK).unwrap();
+        }
+
+        // generate proof
+        let proof = proof::gen_proof_aes_256_ctr(
+            &witness,
+            AES_256_CRT_WTNS,
+            AES_256_CRT_R1CS,
+            AES_256_CRT_PK,
+        )
+        .unwrap();
+
+        // verify it again with the persisted verifying key
+        let vk = descriptor.verifying_key(AES_256_CRT_VK).unwrap();
+        descriptor.verify(&vk, &proof.proof, &proof.public_inputs).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_circuit_descriptor() {
+        use proof::{CircuitDescriptor, Encoding};
+
+        let descriptor = CircuitDescriptor::aes_gcm("missing.wasm", "missing.r1cs", 20);
+        assert_eq!(descriptor.inputs[0].len, 16);
+        assert_eq!(descriptor.outputs.iter().map(|s| s.len).sum::<usize>(), 36);
+        let siv = CircuitDescriptor::aes_gcm_siv("missing.wasm", "missing.r1cs", 32, 16);
+        assert_eq!(siv.inputs[3].encoding, Encoding::Bits);
+        assert_eq!(siv.inputs[3].len, 384);
+
+        // inputs are checked against the descriptor before the artifacts are loaded
+        let pk = proof::setup_keys(square_r1cs(1)).unwrap();
+        let ghash = CircuitDescriptor::ghash("missing.wasm", "missing.r1cs", 2);
+        let err = ghash.prove(&pk, &[("HashKey", &[0; 16]), ("msg", &[0; 16])]).unwrap_err();
+        assert!(err.to_string().contains("input msg must be 32 signals"));
+        let err = ghash.prove(&pk, &[("H", &[0; 16]), ("msg", &[0; 32])]).unwrap_err();
+        assert!(err.to_string().contains("expected input HashKey"));
+        assert!(ghash.prove(&pk, &[("HashKey", &[0; 16])]).is_err());
+
+        // a missing artifact is an error, not a panic
+        let err = ghash.prove(&pk, &[("HashKey", &[0; 16]), ("msg", &[0; 32])]).unwrap_err();
+        assert!(matches!(err, error::Error::Artifact { .. }));
+        assert!(matches!(ghash.proving_key("missing.pk"), Err(error::Error::Artifact { .. })));
+        let err = utils::parse_bit_from_field(&ark_bn254::Fr::from(2)).unwrap_err();
+        assert!(matches!(err, error::Error::InvalidSignal(_)));
+    }
+
+    /// `n` chained squarings, `y = x^(2^n)`, with `y` public
+    fn square_r1cs(n: usize) -> ark_circom::circom::R1CS<ark_bn254::Bn254> {
+        use ark_bn254::Fr;
+
+        // wire 0 is the constant 1, wire 1 is y, wires 2.. are x, x^2, ...
+        let one = Fr::from(1);
+        let mut constraints = vec![];
+        for i in 0..n {
+            let out = if i + 1 == n { 1 } else { i + 3 };
+            constraints.push((vec![(i + 2, one)], vec![(i + 2, one)], vec![(out, one)]));
+        }
+        ark_circom::circom::R1CS {
+            num_inputs: 2,
+            num_aux: n,
+            num_variables: n + 2,
+            constraints,
+            wire_mapping: None,
+        }
+    }
+
+    /// A proof of [`square_r1cs`] for `x = 3`, with the proving key set up for it
+    fn square_proof(
+        n: usize,
+    ) -> (ark_groth16::ProvingKey<ark_bn254::Bn254>, ark_groth16::Proof<ark_bn254::Bn254>) {
+        use ark_bn254::{Bn254, Fr};
+        use ark_crypto_primitives::snark::SNARK;
+
+        // 1, y, then x, x^2, ... up to x^(2^(n-1))
+        let mut witness = vec![Fr::from(1), Fr::from(0), Fr::from(3)];
+        for i in 1..n {
+            witness.push(witness[i + 1] * witness[i + 1]);
+        }
+        witness[1] = witness[n + 1] * witness[n + 1];
+
+        let pk = proof::setup_keys(square_r1cs(n)).unwrap();
+        let circuit =
+            ark_circom::CircomCircuit::<Bn254> { r1cs: square_r1cs(n), witness: Some(witness) };
+        let proof = GrothBn::prove(&pk, circuit, &mut ark_std::rand::thread_rng()).unwrap();
+        (pk, proof)
+    }
+
+    #[tokio::test]
+    async fn test_persisted_keys() {
+        use ark_bn254::{Bn254, Fr};
+        use ark_circom::CircomCircuit;
+        use ark_crypto_primitives::snark::SNARK;
+        use ark_std::rand::thread_rng;
+
+        // `c <== a * b`, see tests/fixtures
+        let r1cs_path = "tests/fixtures/mycircuit.r1cs";
+        let descriptor = proof::CircuitDescriptor::new("tests/fixtures/mycircuit.wasm", r1cs_path);
+        let dir = std::env::temp_dir();
+        let pk_path = dir.join("aes_proof_mycircuit.pk").to_string_lossy().to_string();
+        let vk_path = dir.join("aes_proof_mycircuit.vk").to_string_lossy().to_string();
+        descriptor.setup(&pk_path, &vk_path).unwrap();
+
+        // prove 3 * 11 = 33 with the reloaded proving key, verify with the reloaded verifying key
+        let pk = proof::read_proving_key(&pk_path, r1cs_path).unwrap();
+        let vk = proof::read_verifying_key(&vk_path, r1cs_path).unwrap();
+        assert_eq!(vk, pk.vk);
+        let witness = vec![Fr::from(1), Fr::from(33), Fr::from(3), Fr::from(11)];
+        let mut r1cs = descriptor.load_r1cs().unwrap();
+        r1cs.wire_mapping = None;
+        let circuit = CircomCircuit::<Bn254> { r1cs, witness: Some(witness) };
+        let proof = GrothBn::prove(&pk, circuit, &mut thread_rng()).unwrap();
+        let pvk = GrothBn::process_vk(&vk).unwrap();
+        assert!(GrothBn::verify_with_processed_vk(&pvk, &[Fr::from(33)], &proof).unwrap());
+
+        // `c <== a * 2 * b`: the coefficient of b in B, at byte 0x94, goes from 1 to 2
+        let mut bytes = std::fs::read(r1cs_path).unwrap();
+        assert_eq!(bytes[0x94], 1);
+        bytes[0x94] = 2;
+        let other_path = dir.join("aes_proof_mycircuit_2b.r1cs").to_string_lossy().to_string();
+        std::fs::write(&other_path, bytes).unwrap();
+        let other = proof::CircuitDescriptor::new("missing.wasm", &other_path).load_r1cs().unwrap();
+        let r1cs = descriptor.load_r1cs().unwrap();
+        assert_eq!(other.num_variables, r1cs.num_variables);
+        assert_eq!(other.constraints.len(), r1cs.constraints.len());
+        assert_ne!(other.constraints, r1cs.constraints);
+
+        // the keys do not fit the other circuit, though it has the same shape
+        let err = proof::read_proving_key(&pk_path, &other_path).unwrap_err();
+        assert!(matches!(err, error::Error::KeyMismatch(_)));
+        let err = proof::read_verifying_key(&vk_path, &other_path).unwrap_err();
+        assert!(matches!(err, error::Error::KeyMismatch(_)));
+
+        // only snarkjs zkeys are read as zkeys
+        let err = proof::read_zkey(&pk_path, &square_r1cs(1)).unwrap_err();
+        assert!(matches!(err, error::Error::Artifact { .. }));
+    }
+
+    #[tokio::test]
+    async fn test_snarkjs_zkey() {
+        use ark_bn254::{Bn254, Fr};
+        use ark_circom::CircomCircuit;
+        use ark_crypto_primitives::snark::SNARK;
+        use ark_std::rand::thread_rng;
+
+        // `c <== a * b` with its snarkjs zkey, see tests/fixtures
+        let descriptor = proof::CircuitDescriptor::new(
+            "tests/fixtures/mycircuit.wasm",
+            "tests/fixtures/mycircuit.r1cs",
+        );
+        let pk = descriptor.proving_key("tests/fixtures/mycircuit.zkey").unwrap();
+        let vk = descriptor.verifying_key("tests/fixtures/mycircuit.zkey").unwrap();
+        assert_eq!(vk, pk.vk);
+        let exported = snarkjs::read_verifying_key("tests/fixtures/verification_key.json").unwrap();
+        assert_eq!(exported.vk, vk);
+
+        // wires 1, c, a, b for a = 3, b = 11
+        let witness = vec![Fr::from(1), Fr::from(33), Fr::from(3), Fr::from(11)];
+        let mut r1cs = descriptor.load_r1cs().unwrap();
+        r1cs.wire_mapping = None;
+        let circuit = CircomCircuit::<Bn254> { r1cs, witness: Some(witness) };
+        let proof = GrothBn::prove(&pk, circuit, &mut thread_rng()).unwrap();
+        snarkjs::verify(&exported, &proof, &[Fr::from(33)]).unwrap();
+        assert!(snarkjs::verify(&exported, &proof, &[Fr::from(34)]).is_err());
+
+        // the zkey does not fit another circuit
+        let err = proof::read_zkey("tests/fixtures/mycircuit.zkey", &square_r1cs(1)).unwrap_err();
+        assert!(matches!(err, error::Error::KeyMismatch(_)));
+    }
+
+    #[tokio::test]
+    async fn test_snarkjs_json() {
+        use ark_bn254::Fr;
+        use ark_crypto_primitives::snark::SNARK;
+
+        // y = 3^2
+        let (pk, proof) = square_proof(1);
+
+        let dir = std::env::temp_dir();
+        let proof_path = dir.join("aes_proof_square_proof.json").to_string_lossy().to_string();
+        let public_path = dir.join("aes_proof_square_public.json").to_string_lossy().to_string();
+        snarkjs::write_proof(&proof_path, &proof).unwrap();
+        snarkjs::write_public(&public_path, &[Fr::from(9)]).unwrap();
+
+        // the layout `snarkjs groth16 verify` reads
+        let json: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&proof_path).unwrap()).unwrap();
+        assert_eq!(json["protocol"], "groth16");
+        assert_eq!(json["curve"], "bn128");
+        assert_eq!(json["pi_a"][2], "1");
+        assert_eq!(json["pi_b"][2], serde_json::json!(["1", "0"]));
+        assert_eq!(std::fs::read_to_string(&public_path).unwrap(), "[\n  \"9\"\n]");
+
+        // and back
+        let read = snarkjs::read_proof(&proof_path).unwrap();
+        assert_eq!(read, proof);
+        let public_inputs = snarkjs::read_public(&public_path).unwrap();
+        assert!(GrothBn::verify(&pk.vk, &public_inputs, &read).unwrap());
+
+        // the verifying key, verified without the proving key or the r1cs
+        let vk_path = dir.join("aes_proof_square_vk.json").to_string_lossy().to_string();
+        snarkjs::write_verifying_key(&vk_path, &pk.vk).unwrap();
+        let json: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&vk_path).unwrap()).unwrap();
+        assert_eq!(json["nPublic"], 1);
+        assert_eq!(json["IC"].as_array().unwrap().len(), 2);
+        assert_eq!(json["vk_gamma_2"][2], serde_json::json!(["1", "0"]));
+        let pvk = snarkjs::read_verifying_key(&vk_path).unwrap();
+        assert_eq!(pvk.vk, pk.vk);
+        snarkjs::verify(&pvk, &read, &public_inputs).unwrap();
+        let err = snarkjs::verify(&pvk, &read, &[Fr::from(8)]).unwrap_err();
+        assert!(matches!(err, error::Error::Verification));
+        assert!(snarkjs::verify(&pvk, &read, &[]).is_err());
+
+        // only canonical field elements and points on the curve
+        let modulus =
+            "21888242871839275222246405745257275088548364400416034343698204186575808495617";
+        assert!(snarkjs::field_from_string::<Fr>(modulus).is_err());
+        assert!(snarkjs::field_from_string::<Fr>("09").is_err());
+        assert_eq!(snarkjs::field_from_string::<Fr>("0").unwrap(), Fr::from(0));
+        let mut json = snarkjs::SnarkjsProof::new(&proof);
+        json.pi_a[1] = "2".to_string();
+        assert!(json.proof().is_err());
+    }
+
+    /// `solc` from the `SOLC` env var or `PATH`, if it runs
+    fn solc() -> Option<String> {
+        let solc = std::env::var("SOLC").unwrap_or_else(|_| "solc".to_string());
+        let output = std::process::Command::new(&solc).arg("--version").output().ok()?;
+        output.status.success().then_some(solc)
+    }
+
+    /// Deployment bytecode of the contract `name` in `source`
+    fn compile_contract(solc: &str, source: &str, name: &str) -> Vec<u8> {
+        let dir = std::env::temp_dir().join(format!("zkp-aes-solc-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(format!("{}.sol", name));
+        std::fs::write(&path, source).unwrap();
+
+        let output = std::process::Command::new(solc)
+            .arg("--combined-json")
+            .arg("bin")
+            .arg(&path)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+        let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        let key = format!("{}:{}", path.display(), name);
+        hex::decode(json["contracts"][&key]["bin"].as_str().unwrap()).unwrap()
+    }
+
+    /// Deploy `bytecode` to an empty in-memory chain, then make each of `calls` to it
+    fn evm_deploy_and_call(bytecode: Vec<u8>, calls: Vec<Vec<u8>>) -> Vec<Vec<u8>> {
+        use revm::{
+            db::InMemoryDB,
+            primitives::{ExecutionResult, Output, TxKind},
+            Evm,
+        };
+
+        let mut evm = Evm::builder()
+            .with_db(InMemoryDB::default())
+            .modify_tx_env(|tx| {
+                tx.transact_to = TxKind::Create;
+                tx.data = bytecode.into();
+            })
+            .build();
+        let address = match evm.transact_commit().unwrap() {
+            ExecutionResult::Success { output: Output::Create(_, Some(address)), .. } => address,
+            result => panic!("deployment failed: {:?}", result),
+        };
+
+        calls
+            .into_iter()
+            .map(|data| {
+                evm.tx_mut().transact_to = TxKind::Call(address);
+                evm.tx_mut().data = data.into();
+                match evm.transact().unwrap().result {
+                    ExecutionResult::Success { output: Output::Call(bytes), .. } => bytes.to_vec(),
+                    result => panic!("call to {} failed: {:?}", address, result),
+                }
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_solidity_verifier() {
+        use ark_bn254::Fr;
+
+        // y = 3^2
+        let (pk, proof) = square_proof(1);
+
+        let contract = solidity::verifier_contract(&pk.vk);
+        assert!(contract.contains("uint256[1] calldata _pubSignals"));
+        assert!(!contract.contains("IC2x"));
+
+        // selector, A, B, C, then one word per public signal
+        let calldata = solidity::calldata(&proof, &[Fr::from(9)]);
+        assert_eq!(calldata.len(), 4 + 32 * 9);
+
+        // `verifyProof` returns an ABI encoded bool
+        let Some(solc) = solc() else {
+            println!("skipping the verifyProof calls, no solc on PATH or in SOLC");
+            return;
+        };
+        let bytecode = compile_contract(&solc, &contract, "Groth16Verifier");
+        let results = evm_deploy_and_call(bytecode, vec![
+            calldata,
+            solidity::calldata(&proof, &[Fr::from(8)]),
+        ]);
+        let word = |b: u8| [vec![0; 31], vec![b]].concat();
+        assert_eq!(results, vec![word(1), word(0)]);
+    }
+
+    #[tokio::test]
+    async fn test_aes_gcm_128() {
+        use hex_literal::hex;
+
+        // NIST SP 800-38D test case 2, also the first case in `aes-gcm.test.ts`
+        let (ct, tag) = witness::encrypt_gcm(&[0; 16], &[0; 12], &[], &[0; 16]).unwrap();
+        assert_eq!(ct, hex!("0388dace60b6a392f328c2b971b2fe78"));
+        assert_eq!(tag, hex!("ab6e47d42cec13bdf53a67b21257bddf"));
+
+        // cross-checked against `Aes128Gcm` inside
+        let witness = witness::aes_witnesses(witness::CipherMode::GCM128).unwrap();
+        assert_eq!(witness.iv.len(), 12);
+        assert_eq!(witness.aad.len(), 16);
+        assert_eq!(witness.ct.len(), witness.pt.len());
+        assert_eq!(witness.tag.len(), 16);
+
+        // the circuit hashes the AAD transposed
+        let message = witness::aes_gcm_ghash_message(&consts::GCM_AAD, &witness.ct);
+        assert_ne!(message[0], consts::GCM_AAD);
+        assert_eq!(message[0][1], consts::GCM_AAD[4]);
+    }
+
+    #[tokio::test]
+    async fn test_gcm_iv_lengths() {
+        use hex_literal::hex;
+        use witness::{CipherMode, WitnessBuilder};
+
+        // NIST SP 800-38D test cases 5 (64 bit IV) and 6 (480 bit IV)
+        let key = hex!("feffe9928665731c6d6a8f9467308308");
+        let pt = hex!(
+            "d9313225f88406e5a55909c5aff5269a86a7a9531534f7da2e4c303d8a318a72"
+            "1c3c0c95956809532fcf0e2449a6b525b16aedf5aa0de657ba637b39"
+        );
+        let aad = hex!("feedfacedeadbeeffeedfacedeadbeefabaddad2");
+
+        let iv = hex!("cafebabefacedbad");
+        let witness =
+            WitnessBuilder::new(CipherMode::GCM128).key(&key).iv(&iv).aad(&aad).pt(&pt).build();
+        let witness = witness.unwrap();
+        assert_eq!(
+            witness.ct,
+            hex!(
+                "61353b4c2806934a777ff51fa22a4755699b2a714fcdc6f83766e5f97b6c7423"
+                "73806900e49f24b22b097544d4896b424989b5e1ebac0f07c23f4598"
+            )
+        );
+        assert_eq!(witness.tag, hex!("3612d2e79e3b0785561be14aaca2fccb"));
+
+        let iv = hex!(
+            "9313225df88406e555909c5aff5269aa6a7a9538534f7da1e4c303d2a318a728"
+            "c3c0c95156809539fcf0e2429a6b525416aedbf5a0de6a57a637b39b"
+        );
+        let (ct, tag) = witness::encrypt_gcm(&key, &iv, &aad, &pt).unwrap();
+        assert_eq!(
+            ct,
+            hex!(
+                "8ce24998625615b603a033aca13fb894be9112a5c3a211a8ba262a3cca7e2ca7"
+                "01e4a9a4fba43c90ccdcb281d48c7c6fd62875d2aca417034c34aee5"
+            )
+        );
+        assert_eq!(tag, hex!("619cc5aefffe0bfa462af43c1699d050"));
+
+        // J0 on its own, and as the GHASH witness deriving it
+        let j0 = witness::gcm_j0(&key, &iv).unwrap();
+        let ghash = witness::j0_ghash_witness(&key, &iv).unwrap();
+        assert_eq!(ghash.msg.len(), 5);
+        assert_eq!(ghash.tag, j0[..]);
+        assert_eq!(witness::gcm_j0(&key, &[1; 12]).unwrap(), utils::j0(&[1; 12]));
+        assert!(witness::gcm_j0(&key, &[]).is_err());
+        assert!(witness::j0_ghash_witness(&key, &[]).is_err());
+
+        // CTR starts at inc32(J0), so it still matches GCM
+        let ctr = WitnessBuilder::new(CipherMode::Ctr128).key(&key).iv(&iv).pt(&pt).build();
+        assert_eq!(ctr.unwrap().ct, ct);
+    }
+
+    #[tokio::test]
+    async fn test_aes_256_gcm() {
+        use aes::cipher::{BlockEncrypt, KeyInit};
+        use hex_literal::hex;
+        use witness::{CipherMode, WitnessBuilder};
+
+        // NIST SP 800-38D test case 14
+        let witness = WitnessBuilder::new(CipherMode::GCM256)
+            .key(&[0; 32])
+            .iv(&[0; 12])
+            .pt(&[0; 16])
+            .build()
+            .unwrap();
+        assert_eq!(witness.ct, hex!("cea7403d4d606b6e074ec5d3baf39d18"));
+        assert_eq!(witness.tag, hex!("d0d1c8a799996bf0265b98b5d48ab919"));
+
+        // the GHASH witness covers the 32 byte key too, T = S ^ CIPH_K(J0)
+        let ghash = witness::gcm_ghash_witness(&witness).unwrap();
+        let mut tag_mask = utils::j0(&[0; 12]);
+        Aes256::new(&[0; 32].into()).encrypt_block(&mut tag_mask);
+        let tag: Vec<u8> = ghash.tag.iter().zip(tag_mask).map(|(s, m)| s ^ m).collect();
+        assert_eq!(ghash.msg.len(), 2);
+        assert_eq!(tag, witness.tag);
+
+        let witness = witness::aes_witnesses(CipherMode::GCM256).unwrap();
+        assert_eq!(witness.key.len(), 32);
+        assert_eq!(witness.aad.len(), 16);
+        assert_eq!(witness.tag.len(), 16);
+
+        // AESGCM(l) is AES-128 only, there are no circuit inputs to write
+        let err = make_json_witness(&witness, CipherMode::GCM256).unwrap_err();
+        assert!(err.to_string().contains("no AES-256-GCM circuit"));
+    }
+
+    #[tokio::test]
+    async fn test_gcm_aad_lengths() {
+        use aes::{
+            cipher::{BlockEncrypt, KeyInit},
+            Aes128,
+        };
+        use witness::{gcm_ghash_message, CipherMode};
+
+        // 5 and 13 byte AADs pad to one block, len(A) is their bit length
+        let msg = gcm_ghash_message(&[0xaa; 5], &[0xcc; 20]);
+        assert_eq!(msg.len(), 4);
+        assert_eq!(msg[0], [[0xaa; 5].as_slice(), &[0; 11]].concat()[..]);
+        assert_eq!(msg[2], [[0xcc; 4].as_slice(), &[0; 12]].concat()[..]);
+        assert_eq!(msg[3], [0, 0, 0, 0, 0, 0, 0, 40, 0, 0, 0, 0, 0, 0, 0, 160]);
+        assert_eq!(gcm_ghash_message(&[], &[0xcc; 16]).len(), 2);
+
+        let key = consts::KEY_ASCII.as_bytes();
+        let iv = consts::IV_ASCII.as_bytes();
+        let tls13 = tls::Tls13Session::new(key, iv).unwrap().seal(b"hello", 0x17, 0).unwrap();
+        let tls12 = tls::Tls12Session::new(key, &iv[..4]).unwrap().seal(b"hello", 0x17).unwrap();
+        for witness in [tls13.witness, tls12.witness] {
+            // T = GHASH_H(A, C) ^ CIPH_K(J0)
+            let ghash = witness::gcm_ghash_witness(&witness).unwrap();
+            let mut tag_mask = utils::j0(&witness.iv);
+            Aes128::new(key.into()).encrypt_block(&mut tag_mask);
+            let tag: Vec<u8> = ghash.tag.iter().zip(tag_mask).map(|(s, m)| s ^ m).collect();
+            assert_eq!(tag, witness.tag);
+
+            // the fixed 16 byte AAD circuit is refused
+            let err = utils::make_json_witness(&witness, CipherMode::GCM128).unwrap_err();
+            assert!(err.to_string().contains("fixed 16 byte aad"));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_decrypt_witness() {
+        use error::Error;
+        use hex_literal::hex;
+        use witness::{decrypt_witness, CipherMode};
+
+        // NIST SP 800-38D test case 2, ciphertext then tag
+        let ct = hex!("0388dace60b6a392f328c2b971b2fe78 ab6e47d42cec13bdf53a67b21257bddf");
+        let witness = decrypt_witness(CipherMode::GCM128, &[0; 16], &[0; 12], &[], &ct).unwrap();
+        assert_eq!(witness.pt, [0; 16]);
+        assert_eq!(witness.tag, ct[16..]);
+
+        // every AEAD mode decrypts what the builder encrypts
+        for mode in [CipherMode::GCM128, CipherMode::GCM256, CipherMode::GcmSiv] {
+            let sealed = witness::aes_witnesses(mode).unwrap();
+            let ct = [sealed.ct.as_slice(), &sealed.tag].concat();
+            let opened = decrypt_witness(mode, &sealed.key, &sealed.iv, &sealed.aad, &ct).unwrap();
+            assert_eq!(opened.pt, sealed.pt);
+            assert_eq!(opened.ct, sealed.ct);
+
+            // a flipped bit in the tag is a typed error
+            let mut tampered = ct.clone();
+            *tampered.last_mut().unwrap() ^= 1;
+            let err = decrypt_witness(mode, &sealed.key, &sealed.iv, &sealed.aad, &tampered);
+            assert!(matches!(err, Err(Error::TagMismatch(m)) if m == mode));
+        }
+
+        // no tag in CTR mode, and only 96 bit IVs
+        let err = decrypt_witness(CipherMode::Ctr128, &[0; 16], &[0; 12], &[], &ct);
+        assert!(matches!(err, Err(Error::Input(_))));
+        let err = decrypt_witness(CipherMode::GCM128, &[0; 16], &[0; 8], &[], &ct);
+        assert!(matches!(err, Err(Error::Input(_))));
+    }
+
+    #[tokio::test]
+    async fn test_witness_builder() {
+        use hex_literal::hex;
+
+        // the self generated case in `aes-gcm.test.ts`
+        let witness = witness::WitnessBuilder::new(witness::CipherMode::GCM128)
+            .key(&hex!("31313131313131313131313131313131"))
+            .iv(&hex!("313131313131313131313131"))
+            .pt(b"testhello0000000")
+            .aad(&[0; 16])
+            .build()
+            .unwrap();
+        assert_eq!(witness.key, hex!("31313131313131313131313131313131"));
+        assert_eq!(witness.iv, hex!("313131313131313131313131"));
+        assert_eq!(witness.ct, hex!("2929d2bb1ae94804402b8e776e0d3356"));
+
+        // CTR matches the GCM ciphertext under the same key and nonce
+        let ctr = witness::WitnessBuilder::new(witness::CipherMode::Ctr128)
+            .key(&witness.key)
+            .iv(&witness.iv)
+            .pt(&witness.pt)
+            .build()
+            .unwrap();
+        assert_eq!(ctr.ct, witness.ct);
+
+        // a 128 bit key is rejected by the 256 bit modes
+        assert!(witness::WitnessBuilder::new(witness::CipherMode::GCM256)
+            .key(&witness.key)
+            .iv(&witness.iv)
+            .build()
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_key_expansion() {
+        use hex_literal::hex;
+
+        // FIPS-197 appendix A.1, also `key_expansion.test.ts`
+        let words = key_expansion::expand_key(&hex!("2b7e151628aed2a6abf7158809cf4f3c")).unwrap();
+        assert_eq!(words.len(), 44);
+        assert_eq!(words[4], hex!("a0fafe17"));
+        assert_eq!(words[43], hex!("b6630ca6"));
+
+        // FIPS-197 appendix A.3
+        let words = key_expansion::expand_key(&hex!(
+            "603deb1015ca71be2b73aef0857d77811f352c073b6108d72d9810a30914dff4"
+        ))
+        .unwrap();
+        assert_eq!(words.len(), 60);
+        assert_eq!(words[8], hex!("9ba35411"));
+        assert_eq!(words[59], hex!("706c631e"));
+    }
+
+    #[tokio::test]
+    async fn test_encrypt_tls() {
+        use aes_gcm::{
+            aead::{Aead, NewAead, Payload},
+            Aes128Gcm,
+        };
+
+        let key = consts::KEY_ASCII.as_bytes();
+        let iv = consts::IV_ASCII.as_bytes();
+        let message = consts::MESSAGE.as_bytes();
+        let record = witness::encrypt_tls(message, 0x16, 3, key, iv, 7).unwrap();
+
+        // header: application data, TLS 1.2 legacy version, length of content, type, padding, tag
+        let len = message.len() + 1 + 3 + 16;
+        assert_eq!(record[..5], utils::make_tls13_aad(len));
+        assert_eq!(record.len(), 5 + len);
+
+        // opens with the per-record nonce and the header as aad
+        let nonce = utils::make_nonce(iv.try_into().unwrap(), 7);
+        let inner = Aes128Gcm::new_from_slice(key)
+            .unwrap()
+            .decrypt((&nonce).into(), Payload { msg: &record[5..], aad: &record[..5] })
+            .unwrap();
+        assert_eq!(inner, [message, &[0x16, 0, 0, 0]].concat());
+
+        // a different sequence number gives a different record
+        let next = witness::encrypt_tls(message, 0x16, 3, key, iv, 8).unwrap();
+        assert_ne!(record[5..], next[5..]);
+    }
+
+    #[tokio::test]
+    async fn test_tls13_session() {
+        let key = consts::KEY_ASCII.as_bytes();
+        let iv = consts::IV_ASCII.as_bytes();
+        let mut session = tls::Tls13Session::new(key, iv).unwrap();
+
+        // a response over three records, the last one short
+        let response = vec![b'a'; tls::MAX_FRAGMENT_LEN * 2 + 100];
+        let records = session.seal_response(&response).unwrap();
+        assert_eq!(records.len(), 3);
+        assert_eq!(session.seq(), 3);
+
+        for (seq, record) in records.iter().enumerate() {
+            let seq = seq as u64;
+            assert_eq!(record.seq, seq);
+            assert_eq!(record.witness.iv, utils::make_nonce(iv.try_into().unwrap(), seq));
+            assert_eq!(record.witness.aad, record.record[..5]);
+            assert_eq!(record.witness.pt.last(), Some(&tls::APPLICATION_DATA));
+            assert_eq!(
+                record.record,
+                witness::encrypt_tls(
+                    &record.witness.pt[..record.witness.pt.len() - 1],
+                    0x17,
+                    0,
+                    key,
+                    iv,
+                    seq
+                )
+                .unwrap()
+            );
+        }
+        assert_eq!(records[2].record.len(), 5 + 100 + 1 + 16);
+
+        // the sequence number carries on for later records
+        let alert = session.seal(&[1, 0], 0x15, 2).unwrap();
+        assert_eq!(alert.seq, 3);
+        assert_eq!(alert.witness.pt, [1, 0, 0x15, 0, 0]);
+    }
+
+    #[tokio::test]
+    async fn test_tls12_session() {
+        use hex_literal::hex;
+
+        assert_eq!(
+            utils::make_tls12_aad(0x0102, 0x17, 0x1234),
+            hex!("0000000000000102 17 0303 1234")
+        );
+        assert_eq!(
+            utils::make_tls12_nonce(hex!("a0a1a2a3"), hex!("0000000000000005")),
+            hex!("a0a1a2a3 0000000000000005")
+        );
+
+        let key = consts::KEY_ASCII.as_bytes();
+        let salt = hex!("a0a1a2a3");
+        let mut client = tls::Tls12Session::new(key, &salt).unwrap();
+        let mut server = tls::Tls12Session::new(key, &salt).unwrap();
+
+        // header, explicit nonce = seq, ciphertext, tag
+        let sealed = client.seal(consts::MESSAGE.as_bytes(), 0x17).unwrap();
+        let record = &sealed.record;
+        let len = 8 + consts::MESSAGE.len() + 16;
+        assert_eq!(record[..5], [0x17, 0x3, 0x3, 0, len as u8]);
+        assert_eq!(record[5..13], 0u64.to_be_bytes());
+        assert_eq!(sealed.witness.iv, utils::make_tls12_nonce(salt, [0; 8]));
+        assert_eq!(sealed.witness.aad, utils::make_tls12_aad(0, 0x17, consts::MESSAGE.len()));
+        assert_eq!(sealed.witness.pt, consts::MESSAGE.as_bytes());
+
+        // the peer opens it into the same witness
+        let opened = server.open(record).unwrap();
+        assert_eq!(opened.witness.ct, sealed.witness.ct);
+        assert_eq!(opened.witness.tag, sealed.witness.tag);
+
+        // the next record uses the next sequence number, and fails at the wrong one
+        let second = client.seal(b"second", 0x17).unwrap();
+        assert_eq!(second.record[5..13], 1u64.to_be_bytes());
+        assert!(tls::Tls12Session::new(key, &salt).unwrap().open(&second.record).is_err());
+        assert_eq!(server.open(&second.record).unwrap().witness.pt, b"second");
+
+        // a tampered tag does not authenticate
+        let mut tampered = client.seal(b"third", 0x17).unwrap().record;
+        *tampered.last_mut().unwrap() ^= 1;
+        assert!(server.open(&tampered).is_err());
+        assert!(utils::parse_tls12_record(&tampered[..20]).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_cipher_witness() {
+        use hex_literal::hex;
+
+        // `cipher.test.ts` Cipher#2, the state is column-major
+        let key = hex!("2b7e151628aed2a6abf7158809cf4f3c");
+        let cipher = witness::cipher_witness(&key, &hex!("f0f1f2f3f4f5f6f7f8f9fafbfcfdfeff"));
+        assert_eq!(cipher.block[0], hex!("f0f4f8fc"));
+        assert_eq!(cipher.cipher, [
+            hex!("ec98f2ea"),
+            hex!("8c60d29e"),
+            hex!("df7c16a1"),
+            hex!("73b075e4")
+        ]);
+
+        // the vanilla circuit takes the whole key schedule as k1
+        let key_expansion = witness::key_expansion_witness(&key).unwrap();
+        assert_eq!(key_expansion.keyExpanded.len(), 44);
+        assert_eq!(key_expansion.keyExpanded.concat().len() * 8, 1408);
+    }
+
+    #[tokio::test]
+    async fn test_cipher_trace() {
+        use hex_literal::hex;
+
+        // FIPS-197 appendix B
+        let key = hex!("2b7e151628aed2a6abf7158809cf4f3c");
+        let trace = trace::cipher_trace(&key, &hex!("3243f6a8885a308d313198a2e0370734")).unwrap();
+        let stream = |state: &State| utils::to_stream(&[*state], 16);
+        assert_eq!(stream(&trace.addRoundKey[0]), hex!("193de3bea0f4e22b9ac68d2ae9f84808"));
+        assert_eq!(stream(&trace.subBytes[0]), hex!("d42711aee0bf98f1b8b45de51e415230"));
+        assert_eq!(stream(&trace.shiftRows[0]), hex!("d4bf5d30e0b452aeb84111f11e2798e5"));
+        assert_eq!(stream(&trace.mixColumns[0]), hex!("046681e5e0cb199a48f8d37a2806264c"));
+        assert_eq!(stream(&trace.cipher), hex!("3925841d02dc09fbdc118597196a0b32"));
+        assert_eq!(
+            (trace.addRoundKey.len(), trace.subBytes.len(), trace.mixColumns.len()),
+            (11, 10, 9)
+        );
+
+        // `Cipher()` matches the trace, and so does AES-256, FIPS-197 appendix C.3
+        let cipher = witness::cipher_witness(&key, &hex!("3243f6a8885a308d313198a2e0370734"));
+        assert_eq!(cipher.cipher, trace.cipher);
+        let key = hex!("000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f");
+        let trace = trace::cipher_trace(&key, &hex!("00112233445566778899aabbccddeeff")).unwrap();
+        assert_eq!(stream(&trace.cipher), hex!("8ea2b7ca516745bfeafc49904b496089"));
+    }
+
+    #[tokio::test]
+    async fn test_gctr_fold() {
+        use hex_literal::hex;
+
+        // the two block cases in `aes-gctr-fold.test.ts`
+        let key = hex!("31313131313131313131313131313131");
+        let iv = hex!("313131313131313131313131");
+        let pt_block = b"testhello0000000";
+        let ct_1 = hex!("2929d2bb1ae94804402b8e776e0d3356");
+        let ct_2 = hex!("26756530713e4c065af1d3c4f56e0204");
+
+        let fold = fold::gctr_fold_witnesses(&key, &iv, &[0; 16], &pt_block.repeat(2)).unwrap();
+        assert_eq!(fold.z0, [[0; 64].as_slice(), &[0, 0, 0, 1]].concat());
+        assert_eq!(fold.private_inputs.len(), 2);
+        assert_eq!(fold.private_inputs[1].plainText, pt_block);
+
+        let first = [pt_block.as_slice(), &[0; 16], &ct_1, &[0; 16], &[0, 0, 0, 2]].concat();
+        let second = [pt_block.as_slice(), pt_block, &ct_1, &ct_2, &[0, 0, 0, 3]].concat();
+        assert_eq!(fold.step_outs, vec![first, second]);
+
+        // the native fold step reproduces the whole chain
+        let mut step_in = fold.z0.clone();
+        for (inputs, step_out) in fold.private_inputs.iter().zip(&fold.step_outs) {
+            step_in = fold::gctr_fold_step(&step_in, inputs).unwrap();
+            assert_eq!(&step_in, step_out);
+        }
+        let state = fold::decode_fold_state(&step_in).unwrap();
+        assert_eq!(state.plaintext, pt_block.repeat(2));
+        assert_eq!(state.ciphertext, [ct_1, ct_2].concat());
+        assert_eq!(state.counter, 3);
+
+        // the fold takes a 96 bit IV as is, other lengths would need J0 from GHASH
+        let err = fold::gctr_fold_witnesses(&key, &[0x31; 16], &[0; 16], pt_block).unwrap_err();
+        assert!(err.to_string().contains("iv must be 12 bytes"));
+
+        // a zero counter is p - 1 in the field: the plaintext write marks index 0 as its end and
+        // zeroes everything past the block, the ciphertext lands on the last plaintext block
+        let step_in = [[1; 64].as_slice(), &[0, 0, 0, 0]].concat();
+        let step_out = fold::gctr_fold_step(&step_in, &fold.private_inputs[0]).unwrap();
+        let state = fold::decode_fold_state(&step_out).unwrap();
+        assert_eq!(state.plaintext[..16], pt_block[..]);
+        assert_ne!(state.plaintext[16..], [0; 16]);
+        assert_eq!(state.ciphertext, [0; 32]);
+        assert_eq!(state.counter, 1);
+    }
+
+    #[tokio::test]
+    async fn test_ghash_witness() {
+        use hex_literal::hex;
+
+        // RFC 8452 appendix A, also `ghash.test.ts`
+        let ghash = witness::ghash_witness(&hex!("25629347589242761d31f826ba4b757b"), &[
+            hex!("4f4f95668c83dfb6401762bb2d01a262"),
+            hex!("d1a24ddd2721d006bbe45f20d3c9f362"),
+        ]);
+        assert_eq!(ghash.tag, hex!("bd9b3997046731fb96251b91f9c99d7a"));
+
+        // `ghash_gfmul.test.ts`
+        let x_one = hex!("80000000000000000000000000000000");
+        assert_eq!(witness::ghash_mul_witness(&x_one, &x_one).out, x_one);
+        let mul = witness::ghash_mul_witness(
+            &hex!("aae06992acbf52a3e8f4a96ec9300bd7"),
+            &hex!("98e7247c07f0fe411c267e4384b0f600"),
+        );
+        assert_eq!(mul.out, hex!("90e87315fb7d4e1b4092ec0cbfda5d7d"));
+        let mut one = [0; 16];
+        one[15] = 1;
+        assert_eq!(
+            witness::ghash_mul_witness(&one, &one).out,
+            hex!("e6080000000000000000000000000003")
+        );
+
+        // AAD, one ciphertext block and the length block, keyed by H for the zero key
+        let witness = witness::WitnessBuilder::new(witness::CipherMode::GCM128)
+            .key(&[0; 16])
+            .iv(&[0; 12])
+            .pt(&[0; 16])
+            .aad(&[0; 16])
+            .build()
+            .unwrap();
+        let ghash = witness::aes_gcm_ghash_witness(&witness).unwrap();
+        assert_eq!(ghash.HashKey, hex!("66e94bd4ef8a2c3b884cfa59ca342b2e"));
+        assert_eq!(ghash.msg.len(), 3);
+        assert_eq!(ghash.msg[1], hex!("0388dace60b6a392f328c2b971b2fe78"));
     }
 
     // AES GCM multiple blocks of data
@@ -74,6 +935,7 @@ mod tests {
             aead::{generic_array::GenericArray, Aead, NewAead, Payload},
             Aes128Gcm,
         };
+        use hex_literal::hex;
 
         let test_key = [
             0x31, 0x31, 0x31, 0x31, 0x31, 0x31, 0x31, 0x31, 0x31, 0x31, 0x31, 0x31, 0x31, 0x31,
@@ -92,10 +954,57 @@ mod tests {
         let nonce = GenericArray::from_slice(&test_iv);
         let ct = cipher.encrypt(nonce, aes_payload).expect("error generating ct");
 
-        println!("key={}", hex::encode(test_key));
-        println!("iv={}", hex::encode(test_iv));
-        println!("msg={}", hex::encode(payload));
-        println!("ct={}", hex::encode(ct));
+        // the spec ciphertext of every prefix length, including partial last blocks
+        for l in [1, 15, 16, 17, 37, 160] {
+            let witness = witness::WitnessBuilder::new(witness::CipherMode::GCM128)
+                .key(&test_key)
+                .iv(&test_iv)
+                .pt(&payload[..l])
+                .aad(&[0; 16])
+                .build()
+                .unwrap();
+            assert_eq!(witness.ct, ct[..l]);
+
+            // AAD block, ceil(l / 16) ciphertext blocks, length block
+            let message = witness::aes_gcm_ghash_message(&[0; 16], &witness.ct);
+            assert_eq!(message.len(), l.div_ceil(16) + 2);
+            assert_eq!(message[1..=l / 16].concat(), witness.ct[..l / 16 * 16]);
+            if !l.is_multiple_of(16) {
+                // `ToBlocks` marks the end of a partial block with a one
+                assert_eq!(message[l / 16 + 1][..l % 16], witness.ct[l / 16 * 16..]);
+                assert_eq!(message[l / 16 + 1][l % 16], 1);
+            }
+
+            // `GCTR(INPUT_LEN)` reuses the keystream of the block before a partial last block, and
+            // does not compile for fewer than 16 bytes
+            let ghash = witness::aes_gcm_ghash_witness(&witness);
+            if l < 16 || !l.is_multiple_of(16) {
+                let err = utils::make_json_witness(&witness, witness::CipherMode::GCM128);
+                assert!(err.unwrap_err().to_string().contains("whole blocks"));
+                assert!(ghash.unwrap_err().to_string().contains("whole blocks"));
+            } else {
+                assert_eq!(ghash.unwrap().msg.len(), l / 16 + 2);
+            }
+        }
+
+        // the counter's low byte starts at 2, the 255th block would carry into the IV
+        for (blocks, ok) in [(254, true), (255, false)] {
+            let witness = witness::WitnessBuilder::new(witness::CipherMode::GCM128)
+                .key(&test_key)
+                .iv(&test_iv)
+                .pt(&vec![0; blocks * 16])
+                .aad(&[0; 16])
+                .build()
+                .unwrap();
+            assert_eq!(witness::aes_gcm_ghash_witness(&witness).is_ok(), ok);
+        }
+
+        // the first block matches the self generated case in `aes-gcm.test.ts`
+        assert_eq!(ct[..16], hex!("2929d2bb1ae94804402b8e776e0d3356"));
+
+        // length block for a single block message, as aes-gcm.circom writes it
+        let message = witness::aes_gcm_ghash_message(&[0; 16], &ct[..16]);
+        assert_eq!(message[2], hex!("00000100000000000000000000800000"));
     }
 
     #[tokio::test]
@@ -1,112 +1,530 @@
 //! Generate proofs with ark-circom with the circom artifacts and the generated witness
 
-use std::fs::File;
+use std::{
+    fs::File,
+    io::{BufReader, BufWriter, Read, Write},
+};
 
+use aes::cipher::{generic_array::GenericArray, KeyIvInit, StreamCipher};
 use ark_bn254::{Bn254, Fr};
-use ark_circom::{circom::R1CSFile, CircomBuilder, CircomConfig};
+use ark_circom::{
+    circom::{R1CSFile, R1CS},
+    CircomBuilder, CircomCircuit, CircomConfig, CircomReduction,
+};
 use ark_crypto_primitives::snark::SNARK;
-use ark_groth16::Groth16;
+use ark_groth16::{Groth16, Proof, ProvingKey, VerifyingKey};
+use ark_relations::r1cs::{ConstraintMatrices, ConstraintSynthesizer, ConstraintSystem};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
 use ark_std::rand::thread_rng;
+use sha3::{Digest, Sha3_256};
 
-type GrothBn = Groth16<Bn254>;
-use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystem};
+/// snarkjs' R1CS-to-QAP reduction, so that keys from a snarkjs ceremony prove here and proofs
+/// from here verify in snarkjs
+type GrothBn = Groth16<Bn254, CircomReduction>;
 
 use crate::{
-    utils::{bits_to_u8, parse_bit_from_field, push_bytes_as_bits},
+    error::{ensure, Error, Result},
+    snarkjs,
+    utils::{
+        bits_to_u8, bytes_to_bits, parse_bit_from_field, parse_byte_from_field, AesCtr256Inputs,
+    },
     witness::Witness,
+    Aes256Ctr32BE,
 };
 
-/// load up the circom
-/// generate a witness
-/// generate the proof
-/// check plaintext
-/// check success bit
-pub fn gen_proof_aes_gcm_siv(witness: &Witness, wtns: &str, r1cs: &str) {
-    println!("prep config");
-
-    // read from disk
-    let cfg = CircomConfig::<Bn254>::new(wtns, r1cs).unwrap();
-
-    println!("prep builder");
-    let mut circom_builder = CircomBuilder::new(cfg);
-
-    // TODO(TK 2024-08-06): replace with const
-    // No AAD, but the circuit is sensitive to it. Needs 128 bits.
-    // aes ctr has no aad
-    let aad = [0; 16];
-
-    // TODO(TK 2024-08-06):
-    // code smell: can't tell what this is doing, even by looking at source
-    //
-    // abstract deeper to initialize witness builder
-    // to avoid manipulating circom builder in place
-    circom_builder = push_bytes_as_bits(circom_builder, "K1", &witness.key);
-    circom_builder = push_bytes_as_bits(circom_builder, "N", &witness.iv);
-    circom_builder = push_bytes_as_bits(circom_builder, "AAD", &aad);
-    circom_builder = push_bytes_as_bits(circom_builder, "CT", &witness.ct);
-
-    // read r1cs
-    let r1cs = R1CSFile::<Bn254>::new(File::open(r1cs).unwrap()).unwrap();
-
-    println!("header.n_wires={:?}", r1cs.header.n_wires);
-    println!("header.n_pub_out={:?}", r1cs.header.n_pub_out);
-    println!("header.n_pub_in={:?}", r1cs.header.n_pub_in);
-    println!("header.n_prv_in={:?}", r1cs.header.n_prv_in);
-    println!("header.n_labels={:?}", r1cs.header.n_labels);
-    println!("header.n_constraints={:?}", r1cs.header.n_constraints);
-    println!("inputs={:?}", circom_builder.inputs);
+/// How the values of a signal are laid out
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    /// one signal per bit, most significant bit first, as the Electron Labs circuits take them
+    Bits,
+    /// one signal per byte, as the `aes-gcm` circuits take them
+    Bytes,
+}
 
-    // create an empty instance for setting it up
-    println!("setup builder");
-    let circom = circom_builder.setup();
-    println!("circuit facts: inputs={}", circom.r1cs.num_inputs);
+/// A named signal array, `len` field elements long. Multidimensional signals are flattened in
+/// row-major order, as circom does.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Signal {
+    pub name:     String,
+    pub encoding: Encoding,
+    pub len:      usize,
+}
 
-    let mut rng = thread_rng();
+/// Circuit artifacts, with the input signals in the order they are pushed and the public outputs
+/// in the order the circuit declares them
+#[derive(Debug, Clone)]
+pub struct CircuitDescriptor {
+    pub wtns:    String,
+    pub r1cs:    String,
+    pub inputs:  Vec<Signal>,
+    pub outputs: Vec<Signal>,
+}
+
+/// A verified proof, with its public outputs decoded to bytes
+#[derive(Debug)]
+pub struct CircuitProof {
+    pub proof:         Proof<Bn254>,
+    pub vk:            VerifyingKey<Bn254>,
+    pub public_inputs: Vec<Fr>,
+    pub outputs:       Vec<(String, Vec<u8>)>,
+}
+
+impl CircuitProof {
+    /// The decoded bytes of a named output, bits are packed 8 to a byte
+    pub fn output(&self, name: &str) -> Option<&[u8]> {
+        self.outputs.iter().find(|(output, _)| output == name).map(|(_, bytes)| bytes.as_slice())
+    }
+}
+
+impl CircuitDescriptor {
+    pub fn new(wtns: &str, r1cs: &str) -> Self {
+        Self {
+            wtns:    wtns.to_string(),
+            r1cs:    r1cs.to_string(),
+            inputs:  vec![],
+            outputs: vec![],
+        }
+    }
+
+    /// Add an input signal of `len` field elements
+    pub fn input(mut self, name: &str, encoding: Encoding, len: usize) -> Self {
+        self.inputs.push(Signal { name: name.to_string(), encoding, len });
+        self
+    }
+
+    /// Add a public output signal of `len` field elements
+    pub fn output(mut self, name: &str, encoding: Encoding, len: usize) -> Self {
+        self.outputs.push(Signal { name: name.to_string(), encoding, len });
+        self
+    }
+
+    /// `AESGCM(l)`
+    pub fn aes_gcm(wtns: &str, r1cs: &str, l: usize) -> Self {
+        Self::new(wtns, r1cs)
+            .input("key", Encoding::Bytes, 16)
+            .input("iv", Encoding::Bytes, 12)
+            .input("plainText", Encoding::Bytes, l)
+            .input("aad", Encoding::Bytes, 16)
+            .output("cipherText", Encoding::Bytes, l)
+            .output("authTag", Encoding::Bytes, 16)
+    }
+
+    /// `GHASH(NUM_BLOCKS)`
+    pub fn ghash(wtns: &str, r1cs: &str, num_blocks: usize) -> Self {
+        Self::new(wtns, r1cs)
+            .input("HashKey", Encoding::Bytes, 16)
+            .input("msg", Encoding::Bytes, num_blocks * 16)
+            .output("tag", Encoding::Bytes, 16)
+    }
+
+    /// The Electron Labs AES-256-GCM-SIV decryption circuit, outputs the plaintext and a success
+    /// bit set when the tag verifies
+    pub fn aes_gcm_siv(wtns: &str, r1cs: &str, msg_len: usize, aad_len: usize) -> Self {
+        Self::new(wtns, r1cs)
+            .input("K1", Encoding::Bits, 256)
+            .input("N", Encoding::Bits, 128)
+            .input("AAD", Encoding::Bits, aad_len * 8)
+            .input("CT", Encoding::Bits, (msg_len + 16) * 8)
+            .output("MSG", Encoding::Bits, msg_len * 8)
+            .output("success", Encoding::Bits, 1)
+    }
+
+    /// The Electron Labs `AES256CTR(n_bits_msg)` circuit
+    pub fn aes_256_ctr(wtns: &str, r1cs: &str, msg_len: usize) -> Self {
+        Self::new(wtns, r1cs)
+            .input("msg", Encoding::Bits, msg_len * 8)
+            .input("ctr", Encoding::Bits, 128)
+            .input("ks", Encoding::Bits, 1920)
+            .output("out", Encoding::Bits, msg_len * 8)
+    }
+
+    /// Read the circuit's r1cs
+    pub fn load_r1cs(&self) -> Result<R1CS<Bn254>> {
+        let file = File::open(&self.r1cs)
+            .map_err(|e| Error::Artifact { path: self.r1cs.clone(), reason: e.to_string() })?;
+        let r1cs = R1CSFile::<Bn254>::new(BufReader::new(file))
+            .map_err(|e| Error::Artifact { path: self.r1cs.clone(), reason: e.to_string() })?;
+        Ok(r1cs.into())
+    }
+
+    /// Run the Groth16 setup for the circuit once and write the proving key to `pk_path` and the
+    /// verifying key to `vk_path`, as snarkjs' `verification_key.json` if it ends in `.json`
+    pub fn setup(&self, pk_path: &str, vk_path: &str) -> Result<ProvingKey<Bn254>> {
+        let pk = setup_keys(self.load_r1cs()?)?;
+        write_key(&pk, pk_path, &self.r1cs)?;
+        match vk_path.ends_with(".json") {
+            true => snarkjs::write_verifying_key(vk_path, &pk.vk)?,
+            false => write_key(&pk.vk, vk_path, &self.r1cs)?,
+        }
+        Ok(pk)
+    }
+
+    /// Load the proving key written by [`CircuitDescriptor::setup`], or from a snarkjs `.zkey`
+    pub fn proving_key(&self, path: &str) -> Result<ProvingKey<Bn254>> {
+        match path.ends_with(".zkey") {
+            true => read_zkey(path, &self.load_r1cs()?),
+            false => read_proving_key(path, &self.r1cs),
+        }
+    }
+
+    /// Load the verifying key written by [`CircuitDescriptor::setup`], or from a snarkjs `.zkey` or
+    /// `verification_key.json`
+    pub fn verifying_key(&self, path: &str) -> Result<VerifyingKey<Bn254>> {
+        match path {
+            _ if path.ends_with(".zkey") => Ok(read_zkey(path, &self.load_r1cs()?)?.vk),
+            _ if path.ends_with(".json") => {
+                let vk = snarkjs::read_verifying_key(path)?.vk;
+                check_key(&vk, None, &self.load_r1cs()?)?;
+                Ok(vk)
+            },
+            _ => read_verifying_key(path, &self.r1cs),
+        }
+    }
+
+    /// load up the circom
+    /// push the inputs, given as bytes, in the order and encoding of the descriptor
+    /// generate the proof with `pk` and verify it
+    /// decode the public outputs
+    pub fn prove(&self, pk: &ProvingKey<Bn254>, inputs: &[(&str, &[u8])]) -> Result<CircuitProof> {
+        let mut values = vec![];
+        ensure!(inputs.len() == self.inputs.len(), "expected {} inputs", self.inputs.len());
+        for (signal, (name, bytes)) in self.inputs.iter().zip(inputs) {
+            ensure!(signal.name == *name, "expected input {}, got {}", signal.name, name);
+            let signal_values = match signal.encoding {
+                Encoding::Bits => bytes_to_bits(bytes),
+                Encoding::Bytes => bytes.to_vec(),
+            };
+            ensure!(
+                signal_values.len() == signal.len,
+                "input {} must be {} signals, got {}",
+                signal.name,
+                signal.len,
+                signal_values.len()
+            );
+            values.push((name, signal_values));
+        }
+
+        // `CircomConfig::new` panics on a missing wasm, so check both artifacts up front
+        for path in [&self.wtns, &self.r1cs] {
+            std::fs::metadata(path)
+                .map_err(|e| Error::Artifact { path: path.clone(), reason: e.to_string() })?;
+        }
+
+        let cfg = CircomConfig::<Bn254>::new(&self.wtns, &self.r1cs)
+            .map_err(|e| Error::Artifact { path: self.wtns.clone(), reason: e.to_string() })?;
+        check_key(&pk.vk, Some(pk), &cfg.r1cs)?;
+
+        let mut circom_builder = CircomBuilder::new(cfg);
+        for (name, signal_values) in values {
+            signal_values
+                .into_iter()
+                .for_each(|value| circom_builder.push_input(name, value as u64));
+        }
+
+        // Create the circuit populated with the witness corresponding to the previously
+        // provided inputs
+        let circom = circom_builder.build().map_err(|e| Error::Witness(e.to_string()))?;
+
+        let public_inputs = circom
+            .get_public_inputs()
+            .ok_or_else(|| Error::Witness("no witness to read public inputs from".to_string()))?;
+        let outputs_len: usize = self.outputs.iter().map(|signal| signal.len).sum();
+        ensure!(
+            public_inputs.len() == outputs_len,
+            "circuit has {} public signals, the descriptor {}",
+            public_inputs.len(),
+            outputs_len
+        );
+
+        let mut offset = 0;
+        let mut outputs = vec![];
+        for signal in &self.outputs {
+            let values = &public_inputs[offset..offset + signal.len];
+            offset += signal.len;
+            let bytes = match signal.encoding {
+                Encoding::Bits => values
+                    .chunks(8)
+                    .map(|i| {
+                        let bits =
+                            i.iter().map(parse_bit_from_field).collect::<Result<Vec<_>>>()?;
+                        Ok(bits_to_u8(&bits))
+                    })
+                    .collect::<Result<_>>()?,
+                Encoding::Bytes =>
+                    values.iter().map(parse_byte_from_field).collect::<Result<_>>()?,
+            };
+            outputs.push((signal.name.clone(), bytes));
+        }
+
+        // generate and test constraints
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        circom.clone().generate_constraints(cs.clone())?;
+        if !cs.is_satisfied()? {
+            return Err(Error::Unsatisfied);
+        }
+
+        let mut rng = thread_rng();
+        let proof = GrothBn::prove(pk, circom, &mut rng)?;
+        verify(&pk.vk, &proof, &public_inputs)?;
+
+        Ok(CircuitProof { proof, vk: pk.vk.clone(), public_inputs, outputs })
+    }
+
+    /// Verify a proof of this circuit against its public signals, with a verifying key loaded by
+    /// [`CircuitDescriptor::verifying_key`]
+    pub fn verify(
+        &self,
+        vk: &VerifyingKey<Bn254>,
+        proof: &Proof<Bn254>,
+        public_inputs: &[Fr],
+    ) -> Result<()> {
+        let outputs_len: usize = self.outputs.iter().map(|signal| signal.len).sum();
+        ensure!(
+            public_inputs.len() == outputs_len,
+            "expected {} public signals, got {}",
+            outputs_len,
+            public_inputs.len()
+        );
+        verify(vk, proof, public_inputs)
+    }
+}
+
+/// Random Groth16 parameters for a circuit. Slow for the big circuits, run it once and persist
+/// the keys.
+pub fn setup_keys(r1cs: R1CS<Bn254>) -> Result<ProvingKey<Bn254>> {
+    // create an empty instance for setting it up
+    let mut circom = CircomCircuit::<Bn254> { r1cs, witness: None };
+    circom.r1cs.wire_mapping = None;
 
     // Generates a random common reference string for
     // a circuit using the provided R1CS-to-QAP reduction.
-    println!("gen params");
-    let params = GrothBn::generate_random_parameters_with_reduction(circom, &mut rng).unwrap();
+    let mut rng = thread_rng();
+    Ok(GrothBn::generate_random_parameters_with_reduction(circom, &mut rng)?)
+}
+
+/// Write a proving or verifying key with `ark-serialize`, uncompressed, after the SHA3-256 digest
+/// of the r1cs file it was set up for
+pub fn write_key(key: &impl CanonicalSerialize, path: &str, r1cs: &str) -> Result<()> {
+    let digest = r1cs_digest(r1cs)?;
+    let file = File::create(path)
+        .map_err(|e| Error::Artifact { path: path.to_string(), reason: e.to_string() })?;
+    let mut writer = BufWriter::new(file);
+    writer.write_all(&digest)?;
+    key.serialize_uncompressed(&mut writer)?;
+    Ok(())
+}
+
+/// Read a proving key written by [`write_key`] and check it was set up for the r1cs file `r1cs`.
+///
+/// The curve points are not checked, a corrupt key only yields proofs that do not verify.
+pub fn read_proving_key(path: &str, r1cs: &str) -> Result<ProvingKey<Bn254>> {
+    let mut reader = open_key(path, r1cs)?;
+    Ok(ProvingKey::<Bn254>::deserialize_uncompressed_unchecked(&mut reader)?)
+}
+
+/// Read a verifying key written by [`write_key`] and check it was set up for the r1cs file `r1cs`
+pub fn read_verifying_key(path: &str, r1cs: &str) -> Result<VerifyingKey<Bn254>> {
+    let mut reader = open_key(path, r1cs)?;
+    Ok(VerifyingKey::<Bn254>::deserialize_uncompressed(&mut reader)?)
+}
+
+/// Open a key written by [`write_key`], past the r1cs digest, if the digest matches `r1cs`. Any
+/// change to the circuit changes the digest, even one that keeps the number of wires and
+/// constraints.
+fn open_key(path: &str, r1cs: &str) -> Result<BufReader<File>> {
+    let file = File::open(path)
+        .map_err(|e| Error::Artifact { path: path.to_string(), reason: e.to_string() })?;
+    let mut reader = BufReader::new(file);
+    let mut digest = [0; 32];
+    reader
+        .read_exact(&mut digest)
+        .map_err(|e| Error::Artifact { path: path.to_string(), reason: e.to_string() })?;
+    if digest != r1cs_digest(r1cs)? {
+        return Err(Error::KeyMismatch(format!(
+            "{} was set up for another r1cs than {}",
+            path, r1cs
+        )));
+    }
+    Ok(reader)
+}
+
+fn r1cs_digest(r1cs: &str) -> Result<[u8; 32]> {
+    let bytes = std::fs::read(r1cs)
+        .map_err(|e| Error::Artifact { path: r1cs.to_string(), reason: e.to_string() })?;
+    Ok(Sha3_256::digest(bytes).into())
+}
+
+/// Read the proving key of a snarkjs `.zkey`, from the phase-2 ceremony, and check it was set up
+/// for `r1cs`: the same wires and the same A and B matrices. The verifying key is `pk.vk`.
+pub fn read_zkey(path: &str, r1cs: &R1CS<Bn254>) -> Result<ProvingKey<Bn254>> {
+    let mut file = File::open(path)
+        .map_err(|e| Error::Artifact { path: path.to_string(), reason: e.to_string() })?;
+    // `ark_circom::read_zkey` panics on anything but a zkey
+    let mut magic = [0; 4];
+    file.read_exact(&mut magic)
+        .map_err(|e| Error::Artifact { path: path.to_string(), reason: e.to_string() })?;
+    if &magic != b"zkey" {
+        return Err(Error::Artifact { path: path.to_string(), reason: "not a zkey".to_string() });
+    }
 
-    // Create the circuit populated with the witness corresponding to the previously
-    // provided inputs
-    println!("build builder");
-    let circom = circom_builder.build().unwrap();
+    let mut reader = BufReader::new(File::open(path)?);
+    let (pk, matrices) = ark_circom::read_zkey(&mut reader)?;
+    check_matrices(&matrices, r1cs)?;
+    check_key(&pk.vk, Some(&pk), r1cs)?;
+    Ok(pk)
+}
 
-    println!("get pub input");
-    let inputs = circom.get_public_inputs().unwrap();
-    println!("len={:?}", inputs.len());
+/// The A and B matrices of a zkey against the r1cs constraints, ignoring the order of the terms
+fn check_matrices(matrices: &ConstraintMatrices<Fr>, r1cs: &R1CS<Bn254>) -> Result<()> {
+    // `read_zkey` counts the constant 1 wire among the private ones, `nVars - nPublic`
+    if matrices.num_instance_variables != r1cs.num_inputs
+        || matrices.num_witness_variables != r1cs.num_aux + 1
+        || matrices.num_constraints != r1cs.constraints.len()
+    {
+        return Err(Error::KeyMismatch(format!(
+            "zkey has {} public inputs, {} private wires and {} constraints, the r1cs {}, {} and \
+             {}",
+            matrices.num_instance_variables,
+            matrices.num_witness_variables,
+            matrices.num_constraints,
+            r1cs.num_inputs,
+            r1cs.num_aux,
+            r1cs.constraints.len()
+        )));
+    }
 
-    let output_bytes = inputs
-        .chunks(8)
-        .map(|i| {
-            let bits: Vec<u8> = i.iter().map(parse_bit_from_field).collect();
-            bits_to_u8(&bits)
-        })
-        .collect::<Vec<u8>>();
+    let sorted = |lc: &[(usize, Fr)]| {
+        let mut lc = lc.to_vec();
+        lc.sort_by_key(|(wire, _)| *wire);
+        lc
+    };
+    for (i, (a, b, _)) in r1cs.constraints.iter().enumerate() {
+        let zkey_a: Vec<_> = matrices.a[i].iter().map(|(coeff, wire)| (*wire, *coeff)).collect();
+        let zkey_b: Vec<_> = matrices.b[i].iter().map(|(coeff, wire)| (*wire, *coeff)).collect();
+        if sorted(&zkey_a) != sorted(a) || sorted(&zkey_b) != sorted(b) {
+            return Err(Error::KeyMismatch(format!("constraint {} differs from the r1cs", i)));
+        }
+    }
+    Ok(())
+}
 
-    // generate and test constraints
-    let cs = ConstraintSystem::<Fr>::new_ref();
-    circom.clone().generate_constraints(cs.clone()).unwrap();
-    assert!(cs.is_satisfied().unwrap());
+/// A key only fits the circuit it was set up for: one `gamma_abc_g1` point per public signal and
+/// the constant 1, one `a_query` point per wire, one `l_query` point per private wire and, with
+/// snarkjs' reduction, one `h_query` point per element of the QAP domain. A key for another
+/// circuit with the same shape passes, only keys from [`write_key`] carry the r1cs digest.
+fn check_key(
+    vk: &VerifyingKey<Bn254>,
+    pk: Option<&ProvingKey<Bn254>>,
+    r1cs: &R1CS<Bn254>,
+) -> Result<()> {
+    if vk.gamma_abc_g1.len() != r1cs.num_inputs {
+        return Err(Error::KeyMismatch(format!(
+            "key has {} public inputs, the r1cs {}",
+            vk.gamma_abc_g1.len(),
+            r1cs.num_inputs
+        )));
+    }
+    if let Some(pk) = pk {
+        if pk.a_query.len() != r1cs.num_variables || pk.l_query.len() != r1cs.num_aux {
+            return Err(Error::KeyMismatch(format!(
+                "key has {} wires, the r1cs {}",
+                pk.a_query.len(),
+                r1cs.num_variables
+            )));
+        }
+        let domain_size = (r1cs.constraints.len() + r1cs.num_inputs).next_power_of_two();
+        if pk.h_query.len() != domain_size {
+            return Err(Error::KeyMismatch(format!(
+                "key has a QAP domain of {} points, the r1cs {}",
+                pk.h_query.len(),
+                domain_size
+            )));
+        }
+    }
+    Ok(())
+}
 
-    let proof = GrothBn::prove(&params, circom, &mut rng).unwrap();
-    println!("proof_a={:?}, proof_b={:?}, proof_c={:?}", proof.a, proof.b, proof.c);
+fn verify(vk: &VerifyingKey<Bn254>, proof: &Proof<Bn254>, public_inputs: &[Fr]) -> Result<()> {
+    let pvk = GrothBn::process_vk(vk)?;
+    snarkjs::verify(&pvk, proof, public_inputs)
+}
 
-    println!("process vk");
-    let pvk = GrothBn::process_vk(&params.vk).unwrap();
+impl CircuitProof {
+    /// Write the proof and the public signals as snarkjs' `proof.json` and `public.json`
+    pub fn write_snarkjs(&self, proof_path: &str, public_path: &str) -> Result<()> {
+        snarkjs::write_proof(proof_path, &self.proof)?;
+        snarkjs::write_public(public_path, &self.public_inputs)
+    }
 
-    println!("verify");
-    let verified = GrothBn::verify_with_processed_vk(&pvk, &inputs, &proof).unwrap();
-    assert!(verified);
+    /// Check a named output against the bytes the native implementation computes
+    pub fn check_output(&self, name: &str, expected: &[u8]) -> Result<()> {
+        let actual = self
+            .output(name)
+            .ok_or_else(|| Error::Input(format!("circuit has no output {}", name)))?;
+        if actual != expected {
+            return Err(Error::OutputMismatch {
+                signal:   name.to_string(),
+                expected: expected.to_vec(),
+                actual:   actual.to_vec(),
+            });
+        }
+        Ok(())
+    }
+}
+
+/// load up the circom
+/// generate a witness
+/// generate the proof
+/// check plaintext
+/// check success bit
+pub fn gen_proof_aes_gcm_siv(
+    witness: &Witness,
+    wtns: &str,
+    r1cs: &str,
+    pk: &str,
+) -> Result<CircuitProof> {
+    let descriptor =
+        CircuitDescriptor::aes_gcm_siv(wtns, r1cs, witness.pt.len(), witness.aad.len());
+    let pk = descriptor.proving_key(pk)?;
+    let proof = descriptor.prove(&pk, &[
+        ("K1", &witness.key),
+        ("N", &witness.iv),
+        ("AAD", &witness.aad),
+        ("CT", &witness.ct),
+    ])?;
 
     // Duplicate check, but ensure the plaintext is correct.
-    let pt_bytes = &output_bytes[..witness.pt.len()];
-    println!("Output bytes matches plaintext pt={:?}", pt_bytes);
-    assert!(pt_bytes.iter().zip(witness.pt.iter()).all(|(&a, &b)| a == b));
+    proof.check_output("MSG", &witness.pt)?;
 
     // Check the success bit (auth_tag matches)
-    let success_bit = output_bytes.last().unwrap();
-    println!("Success bit={:?}", success_bit);
-    assert!(success_bit == &1);
+    proof.check_output("success", &[1])?;
+    Ok(proof)
+}
+
+/// load up the circom
+/// generate a witness for the Electron Labs `AES256CTR(n_bits_msg)` circuit
+/// generate the proof
+/// check the ciphertext against `Aes256Ctr32BE`
+///
+/// CTR only, for when the auth tag is checked outside the circuit.
+pub fn gen_proof_aes_256_ctr(
+    witness: &Witness,
+    wtns: &str,
+    r1cs: &str,
+    pk: &str,
+) -> Result<CircuitProof> {
+    // the first counter block is inc32(J0), to match AES-256-GCM
+    let (ctr, ks) = AesCtr256Inputs::ctr_and_ks(witness)?;
+
+    let descriptor = CircuitDescriptor::aes_256_ctr(wtns, r1cs, witness.pt.len());
+    let pk = descriptor.proving_key(pk)?;
+    let proof = descriptor.prove(&pk, &[("msg", &witness.pt), ("ctr", &ctr), ("ks", &ks)])?;
+
+    // the same counter block through rust crypto
+    let mut cipher = Aes256Ctr32BE::new(GenericArray::from_slice(&witness.key), &ctr);
+    let mut ct = witness.pt.clone();
+    cipher.apply_keystream(&mut ct);
+
+    proof.check_output("out", &ct)?;
+    Ok(proof)
 }
@@ -0,0 +1,217 @@
+//! Groth16 proofs, public signals and verifying keys in the snarkjs JSON format, for
+//! `snarkjs groth16 verify` and the JS verifiers. Verifying needs neither ark-circom nor the
+//! circuit artifacts.
+
+use std::{fs::File, io::BufReader, str::FromStr};
+
+use ark_bn254::{Bn254, Fq, Fq2, Fr, G1Affine, G2Affine};
+use ark_crypto_primitives::snark::SNARK;
+use ark_ec::AffineRepr;
+use ark_ff::{PrimeField, Zero};
+use ark_groth16::{Groth16, PreparedVerifyingKey, Proof, VerifyingKey};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::{
+    error::{bail, Error, Result},
+    utils::write_json,
+};
+
+/// `proof.json` as written by `snarkjs groth16 prove`. Points are projective, `[x, y, z]` with
+/// `z = 1`, or `[0, 1, 0]` for the point at infinity, and `Fq2` coordinates are `[c0, c1]`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnarkjsProof {
+    pub pi_a:     [String; 3],
+    pub pi_b:     [[String; 2]; 3],
+    pub pi_c:     [String; 3],
+    pub protocol: String,
+    pub curve:    String,
+}
+
+impl SnarkjsProof {
+    pub fn new(proof: &Proof<Bn254>) -> Self {
+        Self {
+            pi_a:     g1_to_strings(&proof.a),
+            pi_b:     g2_to_strings(&proof.b),
+            pi_c:     g1_to_strings(&proof.c),
+            protocol: "groth16".to_string(),
+            curve:    "bn128".to_string(),
+        }
+    }
+
+    pub fn proof(&self) -> Result<Proof<Bn254>> {
+        if self.protocol != "groth16" || self.curve != "bn128" {
+            bail!("expected a groth16 proof on bn128, got {} on {}", self.protocol, self.curve);
+        }
+        Ok(Proof {
+            a: g1_from_strings(&self.pi_a)?,
+            b: g2_from_strings(&self.pi_b)?,
+            c: g1_from_strings(&self.pi_c)?,
+        })
+    }
+}
+
+/// `verification_key.json` as written by `snarkjs zkey export verificationkey`. snarkjs also
+/// writes `vk_alphabeta_12`, which it does not need to verify, it is ignored here.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnarkjsVerifyingKey {
+    pub protocol:   String,
+    pub curve:      String,
+    pub nPublic:    usize,
+    pub vk_alpha_1: [String; 3],
+    pub vk_beta_2:  [[String; 2]; 3],
+    pub vk_gamma_2: [[String; 2]; 3],
+    pub vk_delta_2: [[String; 2]; 3],
+    pub IC:         Vec<[String; 3]>,
+}
+
+impl SnarkjsVerifyingKey {
+    pub fn new(vk: &VerifyingKey<Bn254>) -> Self {
+        Self {
+            protocol:   "groth16".to_string(),
+            curve:      "bn128".to_string(),
+            nPublic:    vk.gamma_abc_g1.len() - 1,
+            vk_alpha_1: g1_to_strings(&vk.alpha_g1),
+            vk_beta_2:  g2_to_strings(&vk.beta_g2),
+            vk_gamma_2: g2_to_strings(&vk.gamma_g2),
+            vk_delta_2: g2_to_strings(&vk.delta_g2),
+            IC:         vk.gamma_abc_g1.iter().map(g1_to_strings).collect(),
+        }
+    }
+
+    pub fn verifying_key(&self) -> Result<VerifyingKey<Bn254>> {
+        if self.protocol != "groth16" || self.curve != "bn128" {
+            bail!("expected a groth16 key on bn128, got {} on {}", self.protocol, self.curve);
+        }
+        if self.IC.len() != self.nPublic + 1 {
+            bail!(
+                "verifying key has {} IC points for {} public signals",
+                self.IC.len(),
+                self.nPublic
+            );
+        }
+        Ok(VerifyingKey {
+            alpha_g1:     g1_from_strings(&self.vk_alpha_1)?,
+            beta_g2:      g2_from_strings(&self.vk_beta_2)?,
+            gamma_g2:     g2_from_strings(&self.vk_gamma_2)?,
+            delta_g2:     g2_from_strings(&self.vk_delta_2)?,
+            gamma_abc_g1: self.IC.iter().map(g1_from_strings).collect::<Result<_>>()?,
+        })
+    }
+}
+
+/// Write `verification_key.json`
+pub fn write_verifying_key(path: &str, vk: &VerifyingKey<Bn254>) -> Result<()> {
+    write_json(path, &SnarkjsVerifyingKey::new(vk))
+}
+
+/// Read `verification_key.json`, prepared for [`verify`]
+pub fn read_verifying_key(path: &str) -> Result<PreparedVerifyingKey<Bn254>> {
+    let vk = read_json::<SnarkjsVerifyingKey>(path)?.verifying_key()?;
+    Ok(Groth16::<Bn254>::process_vk(&vk)?)
+}
+
+/// Verify a proof against its public signals, as `snarkjs groth16 verify` does
+pub fn verify(
+    pvk: &PreparedVerifyingKey<Bn254>,
+    proof: &Proof<Bn254>,
+    public_inputs: &[Fr],
+) -> Result<()> {
+    if pvk.vk.gamma_abc_g1.len() != public_inputs.len() + 1 {
+        return Err(Error::KeyMismatch(format!(
+            "key has {} public signals, got {}",
+            pvk.vk.gamma_abc_g1.len() - 1,
+            public_inputs.len()
+        )));
+    }
+    if !Groth16::<Bn254>::verify_with_processed_vk(pvk, public_inputs, proof)? {
+        return Err(Error::Verification);
+    }
+    Ok(())
+}
+
+/// Write `proof.json`
+pub fn write_proof(path: &str, proof: &Proof<Bn254>) -> Result<()> {
+    write_json(path, &SnarkjsProof::new(proof))
+}
+
+/// Read `proof.json`
+pub fn read_proof(path: &str) -> Result<Proof<Bn254>> { read_json::<SnarkjsProof>(path)?.proof() }
+
+/// Write `public.json`, the public signals as decimal strings
+pub fn write_public(path: &str, public_inputs: &[Fr]) -> Result<()> {
+    write_json(path, &public_inputs.iter().map(field_to_string).collect::<Vec<_>>())
+}
+
+/// Read `public.json`
+pub fn read_public(path: &str) -> Result<Vec<Fr>> {
+    read_json::<Vec<String>>(path)?.iter().map(|value| field_from_string(value)).collect()
+}
+
+fn read_json<T: DeserializeOwned>(path: &str) -> Result<T> {
+    let file = File::open(path)
+        .map_err(|e| Error::Artifact { path: path.to_string(), reason: e.to_string() })?;
+    Ok(serde_json::from_reader(BufReader::new(file))?)
+}
+
+/// A field element as snarkjs writes it, in decimal
+pub(crate) fn field_to_string<F: PrimeField>(value: &F) -> String {
+    value.into_bigint().to_string()
+}
+
+/// A field element in decimal, refusing anything but the canonical form snarkjs writes
+pub(crate) fn field_from_string<F: PrimeField + FromStr>(value: &str) -> Result<F> {
+    match F::from_str(value) {
+        Ok(field) if field_to_string(&field) == value => Ok(field),
+        _ => bail!("{} is not a field element", value),
+    }
+}
+
+pub(crate) fn g1_to_strings(point: &G1Affine) -> [String; 3] {
+    match point.xy() {
+        Some((x, y)) => [field_to_string(x), field_to_string(y), "1".to_string()],
+        None => ["0".to_string(), "1".to_string(), "0".to_string()],
+    }
+}
+
+pub(crate) fn g2_to_strings(point: &G2Affine) -> [[String; 2]; 3] {
+    let fq2 = |c: &Fq2| [field_to_string(&c.c0), field_to_string(&c.c1)];
+    let pair = |c0: &str, c1: &str| [c0.to_string(), c1.to_string()];
+    match point.xy() {
+        Some((x, y)) => [fq2(x), fq2(y), pair("1", "0")],
+        None => [pair("0", "0"), pair("1", "0"), pair("0", "0")],
+    }
+}
+
+pub(crate) fn g1_from_strings(point: &[String; 3]) -> Result<G1Affine> {
+    let [x, y, z] = point.each_ref().map(|value| field_from_string::<Fq>(value));
+    let (x, y, z) = (x?, y?, z?);
+    if z.is_zero() {
+        return Ok(G1Affine::zero());
+    }
+    if z != Fq::from(1u8) {
+        bail!("G1 point is not normalized, z = {}", field_to_string(&z));
+    }
+    let point = G1Affine::new_unchecked(x, y);
+    if !point.is_on_curve() || !point.is_in_correct_subgroup_assuming_on_curve() {
+        bail!("G1 point is not on the curve");
+    }
+    Ok(point)
+}
+
+pub(crate) fn g2_from_strings(point: &[[String; 2]; 3]) -> Result<G2Affine> {
+    let fq2 = |c: &[String; 2]| -> Result<Fq2> {
+        Ok(Fq2::new(field_from_string(&c[0])?, field_from_string(&c[1])?))
+    };
+    let (x, y, z) = (fq2(&point[0])?, fq2(&point[1])?, fq2(&point[2])?);
+    if z.is_zero() {
+        return Ok(G2Affine::zero());
+    }
+    if z != Fq2::from(1u8) {
+        bail!("G2 point is not normalized");
+    }
+    let point = G2Affine::new_unchecked(x, y);
+    if !point.is_on_curve() || !point.is_in_correct_subgroup_assuming_on_curve() {
+        bail!("G2 point is not on the curve");
+    }
+    Ok(point)
+}
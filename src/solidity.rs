@@ -0,0 +1,223 @@
+//! A Solidity Groth16 verifier for a verifying key, checked with the BN254 precompiles (EIP-196,
+//! EIP-197), and the calldata of `verifyProof` for a proof. The contract is laid out like the one
+//! `snarkjs zkey export solidityverifier` writes.
+
+use ark_bn254::{Bn254, Fq, Fq2, Fr, G1Affine, G2Affine};
+use ark_ec::AffineRepr;
+use ark_ff::{BigInteger, PrimeField, Zero};
+use ark_groth16::{Proof, VerifyingKey};
+use sha3::{Digest, Keccak256};
+
+use crate::{error::Result, snarkjs::field_to_string};
+
+const VERIFIER_TEMPLATE: &str = r#"// SPDX-License-Identifier: GPL-3.0
+pragma solidity >=0.7.0 <0.9.0;
+
+contract Groth16Verifier {
+    // Scalar field size
+    uint256 constant r = <%r%>;
+    // Base field size
+    uint256 constant q = <%q%>;
+
+    // Verification Key data
+<%vk%>
+
+    // Memory data
+    uint16 constant pVk = 0;
+    uint16 constant pPairing = 128;
+
+    uint16 constant pLastMem = 896;
+
+    function verifyProof(
+        uint256[2] calldata _pA,
+        uint256[2][2] calldata _pB,
+        uint256[2] calldata _pC,
+        uint256[<%nPublic%>] calldata _pubSignals
+    ) public view returns (bool) {
+        assembly {
+            function checkField(v) {
+                if iszero(lt(v, r)) {
+                    mstore(0, 0)
+                    return(0, 0x20)
+                }
+            }
+
+            // G1 function to multiply a G1 value(x,y) to value in an address
+            function g1_mulAccC(pR, x, y, s) {
+                let success
+                let mIn := mload(0x40)
+                mstore(mIn, x)
+                mstore(add(mIn, 32), y)
+                mstore(add(mIn, 64), s)
+
+                success := staticcall(sub(gas(), 2000), 7, mIn, 96, mIn, 64)
+
+                if iszero(success) {
+                    mstore(0, 0)
+                    return(0, 0x20)
+                }
+
+                mstore(add(mIn, 64), mload(pR))
+                mstore(add(mIn, 96), mload(add(pR, 32)))
+
+                success := staticcall(sub(gas(), 2000), 6, mIn, 128, pR, 64)
+
+                if iszero(success) {
+                    mstore(0, 0)
+                    return(0, 0x20)
+                }
+            }
+
+            function checkPairing(pA, pB, pC, pubSignals, pMem) -> isOk {
+                let _pPairing := add(pMem, pPairing)
+                let _pVk := add(pMem, pVk)
+
+                mstore(_pVk, IC0x)
+                mstore(add(_pVk, 32), IC0y)
+
+                // Compute the linear combination vk_x
+<%mulAcc%>
+
+                // -A
+                mstore(_pPairing, calldataload(pA))
+                mstore(add(_pPairing, 32), mod(sub(q, calldataload(add(pA, 32))), q))
+
+                // B
+                mstore(add(_pPairing, 64), calldataload(pB))
+                mstore(add(_pPairing, 96), calldataload(add(pB, 32)))
+                mstore(add(_pPairing, 128), calldataload(add(pB, 64)))
+                mstore(add(_pPairing, 160), calldataload(add(pB, 96)))
+
+                // alpha1
+                mstore(add(_pPairing, 192), alphax)
+                mstore(add(_pPairing, 224), alphay)
+
+                // beta2
+                mstore(add(_pPairing, 256), betax1)
+                mstore(add(_pPairing, 288), betax2)
+                mstore(add(_pPairing, 320), betay1)
+                mstore(add(_pPairing, 352), betay2)
+
+                // vk_x
+                mstore(add(_pPairing, 384), mload(add(pMem, pVk)))
+                mstore(add(_pPairing, 416), mload(add(pMem, add(pVk, 32))))
+
+                // gamma2
+                mstore(add(_pPairing, 448), gammax1)
+                mstore(add(_pPairing, 480), gammax2)
+                mstore(add(_pPairing, 512), gammay1)
+                mstore(add(_pPairing, 544), gammay2)
+
+                // C
+                mstore(add(_pPairing, 576), calldataload(pC))
+                mstore(add(_pPairing, 608), calldataload(add(pC, 32)))
+
+                // delta2
+                mstore(add(_pPairing, 640), deltax1)
+                mstore(add(_pPairing, 672), deltax2)
+                mstore(add(_pPairing, 704), deltay1)
+                mstore(add(_pPairing, 736), deltay2)
+
+                let success := staticcall(sub(gas(), 2000), 8, _pPairing, 768, _pPairing, 0x20)
+
+                isOk := and(success, mload(_pPairing))
+            }
+
+            let pMem := mload(0x40)
+            mstore(0x40, add(pMem, pLastMem))
+
+            // Validate that all evaluations ∈ F
+<%checkField%>
+
+            // Validate all evaluations
+            let isValid := checkPairing(_pA, _pB, _pC, _pubSignals, pMem)
+
+            mstore(0, isValid)
+            return(0, 0x20)
+        }
+    }
+}
+"#;
+
+/// Render the verifier contract for `vk`
+pub fn verifier_contract(vk: &VerifyingKey<Bn254>) -> String {
+    let n_public = vk.gamma_abc_g1.len() - 1;
+
+    let mut constants = vec![];
+    let (alphax, alphay) = g1_coordinates(&vk.alpha_g1);
+    constants.push(("alphax".to_string(), alphax));
+    constants.push(("alphay".to_string(), alphay));
+    for (name, point) in [("beta", &vk.beta_g2), ("gamma", &vk.gamma_g2), ("delta", &vk.delta_g2)] {
+        let [x1, x2, y1, y2] = g2_coordinates(point);
+        constants.push((format!("{}x1", name), x1));
+        constants.push((format!("{}x2", name), x2));
+        constants.push((format!("{}y1", name), y1));
+        constants.push((format!("{}y2", name), y2));
+    }
+    for (i, point) in vk.gamma_abc_g1.iter().enumerate() {
+        let (x, y) = g1_coordinates(point);
+        constants.push((format!("IC{}x", i), x));
+        constants.push((format!("IC{}y", i), y));
+    }
+    let vk_constants = constants
+        .iter()
+        .map(|(name, value)| format!("    uint256 constant {} = {};", name, field_to_string(value)))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let mul_acc = (1..=n_public)
+        .map(|i| {
+            format!(
+                "                g1_mulAccC(_pVk, IC{}x, IC{}y, calldataload(add(pubSignals, {})))",
+                i,
+                i,
+                (i - 1) * 32
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    let check_field = (0..n_public)
+        .map(|i| format!("            checkField(calldataload(add(_pubSignals, {})))", i * 32))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    VERIFIER_TEMPLATE
+        .replace("<%r%>", &Fr::MODULUS.to_string())
+        .replace("<%q%>", &Fq::MODULUS.to_string())
+        .replace("<%vk%>", &vk_constants)
+        .replace("<%nPublic%>", &n_public.to_string())
+        .replace("<%mulAcc%>", &mul_acc)
+        .replace("<%checkField%>", &check_field)
+}
+
+/// Write the verifier contract for `vk` to a `.sol` file
+pub fn write_verifier_contract(path: &str, vk: &VerifyingKey<Bn254>) -> Result<()> {
+    Ok(std::fs::write(path, verifier_contract(vk))?)
+}
+
+/// The ABI-encoded call of `verifyProof(_pA, _pB, _pC, _pubSignals)`
+pub fn calldata(proof: &Proof<Bn254>, public_inputs: &[Fr]) -> Vec<u8> {
+    let signature = format!(
+        "verifyProof(uint256[2],uint256[2][2],uint256[2],uint256[{}])",
+        public_inputs.len()
+    );
+    let mut calldata = Keccak256::digest(signature.as_bytes())[..4].to_vec();
+
+    let (ax, ay) = g1_coordinates(&proof.a);
+    let (cx, cy) = g1_coordinates(&proof.c);
+    let words = [ax, ay].into_iter().chain(g2_coordinates(&proof.b)).chain([cx, cy]);
+    words.for_each(|word| calldata.extend(word.into_bigint().to_bytes_be()));
+    public_inputs.iter().for_each(|signal| calldata.extend(signal.into_bigint().to_bytes_be()));
+    calldata
+}
+
+/// `(x, y)`, `(0, 0)` for the point at infinity as EIP-196 encodes it
+fn g1_coordinates(point: &G1Affine) -> (Fq, Fq) {
+    point.xy().map(|(x, y)| (*x, *y)).unwrap_or((Fq::zero(), Fq::zero()))
+}
+
+/// `[x.c1, x.c0, y.c1, y.c0]`, EIP-197 puts the imaginary part first
+fn g2_coordinates(point: &G2Affine) -> [Fq; 4] {
+    let (x, y) = point.xy().map(|(x, y)| (*x, *y)).unwrap_or((Fq2::zero(), Fq2::zero()));
+    [x.c1, x.c0, y.c1, y.c0]
+}
@@ -0,0 +1,138 @@
+//! TLS record witnesses, for proofs over responses that span several records
+
+use serde::Serialize;
+
+use crate::{
+    error::{ensure, Result},
+    utils::{make_nonce, make_tls12_aad, make_tls12_nonce, make_tls13_aad, parse_tls12_record},
+    witness::{decrypt_witness, encrypt_tls, encrypt_tls12, CipherMode, Witness, WitnessBuilder},
+};
+
+/// ContentType::ApplicationData
+pub(crate) const APPLICATION_DATA: u8 = 0x17;
+
+/// Largest `TLSInnerPlaintext` content, 2^14 bytes
+pub(crate) const MAX_FRAGMENT_LEN: usize = 1 << 14;
+
+/// One direction of a TLS 1.3 connection: a traffic key, its static IV and the sequence number of
+/// the next record, see RFC 8446 section 5.3.
+#[derive(Debug)]
+pub struct Tls13Session {
+    key: Vec<u8>,
+    iv:  [u8; 12],
+    seq: u64,
+}
+
+/// A sealed TLS record with the AES-GCM witness behind it
+#[derive(Debug, Serialize)]
+pub struct TlsRecordWitness {
+    pub seq:     u64,
+    /// the whole `TLSCiphertext`, header included
+    pub record:  Vec<u8>,
+    /// `iv` is the per-record nonce. For TLS 1.3 `aad` is the record header and `pt` the
+    /// `TLSInnerPlaintext`, for TLS 1.2 `aad` is the 13 byte seq_num || header and `pt` the
+    /// content.
+    pub witness: Witness,
+}
+
+impl Tls13Session {
+    /// A session for a 16 byte (TLS_AES_128_GCM_SHA256) or 32 byte (TLS_AES_256_GCM_SHA384)
+    /// traffic key, starting at sequence number 0
+    pub fn new(key: &[u8], iv: &[u8]) -> Result<Self> {
+        ensure!(matches!(key.len(), 16 | 32), "TLS 1.3 AES-GCM key must be 16 or 32 bytes");
+        Ok(Self { key: key.to_vec(), iv: iv.try_into()?, seq: 0 })
+    }
+
+    /// Sequence number of the next record
+    pub fn seq(&self) -> u64 { self.seq }
+
+    /// Seal the next record and advance the sequence number
+    pub fn seal(
+        &mut self,
+        message: &[u8],
+        content_type: u8,
+        padding: usize,
+    ) -> Result<TlsRecordWitness> {
+        ensure!(message.len() + 1 + padding <= MAX_FRAGMENT_LEN + 1, "record too long");
+        // the sequence number must not wrap, RFC 8446 section 5.3
+        ensure!(self.seq < u64::MAX, "sequence number exhausted, rekey");
+
+        let mut inner = message.to_vec();
+        inner.push(content_type);
+        inner.resize(inner.len() + padding, 0);
+
+        let mode = if self.key.len() == 16 { CipherMode::GCM128 } else { CipherMode::GCM256 };
+        let witness = WitnessBuilder::new(mode)
+            .key(&self.key)
+            .iv(&make_nonce(self.iv, self.seq))
+            .aad(&make_tls13_aad(inner.len() + 16))
+            .pt(&inner)
+            .build()?;
+
+        let record = encrypt_tls(message, content_type, padding, &self.key, &self.iv, self.seq)?;
+        ensure!(
+            record == [witness.aad.as_slice(), &witness.ct, &witness.tag].concat(),
+            "TLS 1.3 record mismatch"
+        );
+
+        let seq = self.seq;
+        self.seq += 1;
+        Ok(TlsRecordWitness { seq, record, witness })
+    }
+
+    /// Seal an application data stream, one record per item
+    pub fn seal_records<'a>(
+        &mut self,
+        messages: impl IntoIterator<Item = &'a [u8]>,
+    ) -> Result<Vec<TlsRecordWitness>> {
+        messages.into_iter().map(|message| self.seal(message, APPLICATION_DATA, 0)).collect()
+    }
+
+    /// Seal a whole response as application data, split into records of at most 2^14 bytes
+    pub fn seal_response(&mut self, response: &[u8]) -> Result<Vec<TlsRecordWitness>> {
+        self.seal_records(response.chunks(MAX_FRAGMENT_LEN))
+    }
+}
+
+/// One direction of a TLS 1.2 AES-GCM connection: a write key, the 4 byte implicit salt
+/// (`client_write_IV` or `server_write_IV`) and the sequence number of the next record.
+#[derive(Debug)]
+pub struct Tls12Session {
+    key:  Vec<u8>,
+    salt: [u8; 4],
+    seq:  u64,
+}
+
+impl Tls12Session {
+    /// A session for a 16 or 32 byte write key, starting at sequence number 0
+    pub fn new(key: &[u8], salt: &[u8]) -> Result<Self> {
+        ensure!(matches!(key.len(), 16 | 32), "TLS 1.2 AES-GCM key must be 16 or 32 bytes");
+        Ok(Self { key: key.to_vec(), salt: salt.try_into()?, seq: 0 })
+    }
+
+    /// Sequence number of the next record
+    pub fn seq(&self) -> u64 { self.seq }
+
+    /// Seal the next record and advance the sequence number
+    pub fn seal(&mut self, message: &[u8], content_type: u8) -> Result<TlsRecordWitness> {
+        let record = encrypt_tls12(message, content_type, &self.key, &self.salt, self.seq)?;
+        self.open(&record)
+    }
+
+    /// Decrypt the next record received and advance the sequence number
+    pub fn open(&mut self, record: &[u8]) -> Result<TlsRecordWitness> {
+        // the sequence number must not wrap, RFC 5246 section 6.1
+        ensure!(self.seq < u64::MAX, "sequence number exhausted, renegotiate");
+        let parsed = parse_tls12_record(record)?;
+        let nonce = make_tls12_nonce(self.salt, parsed.explicit_nonce);
+        let aad = make_tls12_aad(self.seq, parsed.content_type, parsed.ct.len());
+
+        let mode = if self.key.len() == 16 { CipherMode::GCM128 } else { CipherMode::GCM256 };
+        let ct_and_tag = [parsed.ct, parsed.tag].concat();
+        let witness = decrypt_witness(mode, &self.key, &nonce, &aad, &ct_and_tag)?;
+
+        let seq = self.seq;
+        self.seq += 1;
+        Ok(TlsRecordWitness { seq, record: record.to_vec(), witness })
+    }
+}
@@ -0,0 +1,91 @@
+//! Round-by-round AES trace, mirroring the components of `Cipher()` in `aes/cipher.circom`
+//!
+//! Every state is `[4][4]` column-major, `state[row][column]`, as the circom templates take it, so
+//! a failing witness can be diffed against the trace signal by signal.
+
+use serde::Serialize;
+
+use crate::{
+    error::Result,
+    key_expansion::{expand_key, gmul, sbox},
+    utils::to_blocks,
+    State,
+};
+
+/// Output of every `Cipher()` component, indexed like the component arrays in the circuit
+#[derive(Debug, Serialize)]
+pub struct CipherTrace {
+    pub block:       State,
+    pub key:         Vec<u8>,
+    /// `addRoundKey[i].newState`, `addRoundKey[0]` whitens the input block
+    pub addRoundKey: Vec<State>,
+    /// `subBytes[i].newState`
+    pub subBytes:    Vec<State>,
+    /// `shiftRows[i].newState`
+    pub shiftRows:   Vec<State>,
+    /// `mixColumns[i].out`, the last round has none
+    pub mixColumns:  Vec<State>,
+    pub cipher:      State,
+}
+
+/// Encrypt `block`, recording the state after each step. Takes a 16, 24 or 32 byte key, though
+/// `Cipher()` itself is AES-128 only.
+pub fn cipher_trace(key: &[u8], block: &[u8; 16]) -> Result<CipherTrace> {
+    let words = expand_key(key)?;
+    let round_keys: Vec<&[[u8; 4]]> = words.chunks(4).collect();
+    let rounds = round_keys.len() - 1;
+
+    let block = to_blocks(block)[0];
+    let mut trace = CipherTrace {
+        block,
+        key: key.to_vec(),
+        addRoundKey: vec![add_round_key(&block, round_keys[0])],
+        subBytes: vec![],
+        shiftRows: vec![],
+        mixColumns: vec![],
+        cipher: State::default(),
+    };
+
+    for (round, round_key) in round_keys.iter().enumerate().skip(1) {
+        let state = sub_block(trace.addRoundKey.last().unwrap());
+        trace.subBytes.push(state);
+        let mut state = shift_rows(&state);
+        trace.shiftRows.push(state);
+        if round < rounds {
+            state = mix_columns(&state);
+            trace.mixColumns.push(state);
+        }
+        trace.addRoundKey.push(add_round_key(&state, round_key));
+    }
+
+    trace.cipher = *trace.addRoundKey.last().unwrap();
+    Ok(trace)
+}
+
+/// `SubBlock()`: S-box every byte
+fn sub_block(state: &State) -> State { state.map(|row| row.map(sbox)) }
+
+/// `ShiftRows()`: rotate row `i` left by `i`
+fn shift_rows(state: &State) -> State {
+    let mut out = *state;
+    out.iter_mut().enumerate().for_each(|(i, row)| row.rotate_left(i));
+    out
+}
+
+/// `MixColumns()`: multiply each column by the fixed polynomial {03}x^3 + {01}x^2 + {01}x + {02}
+fn mix_columns(state: &State) -> State {
+    let mut out = State::default();
+    for col in 0..4 {
+        let c: [u8; 4] = std::array::from_fn(|row| state[row][col]);
+        for (row, out_row) in out.iter_mut().enumerate() {
+            out_row[col] =
+                gmul(c[row], 2) ^ gmul(c[(row + 1) % 4], 3) ^ c[(row + 2) % 4] ^ c[(row + 3) % 4];
+        }
+    }
+    out
+}
+
+/// `AddRoundKey()`: the round key is four words, one per column, so it XORs in transposed
+fn add_round_key(state: &State, round_key: &[[u8; 4]]) -> State {
+    std::array::from_fn(|i| std::array::from_fn(|j| state[i][j] ^ round_key[j][i]))
+}
@@ -10,20 +10,25 @@
 //! - AES-GCM can be decrypted by AES-CTR, by skipping the auth tag and setting up the IV correctly.
 
 use aes::{
-    cipher::{BlockEncrypt, InnerIvInit, KeyInit, KeyIvInit, StreamCipher, StreamCipherCore},
-    Aes128,
+    cipher::{BlockEncrypt, KeyInit, KeyIvInit, StreamCipher},
+    Aes128, Aes256,
 };
 use aes_gcm::{
     aead::{generic_array::GenericArray, Aead, NewAead, Payload},
     Aes128Gcm, Aes256Gcm,
 };
-use anyhow::Result;
+use ghash::{universal_hash::UniversalHash, GHash};
 use serde::Serialize;
 
 use crate::{
     consts::*,
-    utils::{apply_keystream, make_nonce, make_tls13_aad},
-    Aes128Ctr32BE, Aes256Ctr32BE, Block, Ctr32BE,
+    error::{bail, ensure, Error, Result},
+    key_expansion::expand_key,
+    utils::{
+        ensure_gctr_len, ghash_j0, inc32, make_nonce, make_tls12_aad, make_tls12_nonce,
+        make_tls13_aad, to_blocks, to_stream,
+    },
+    Aes128Ctr32BE, Aes256Ctr32BE, Block, State,
 };
 
 /// Witness bytes generated by this binary
@@ -33,6 +38,10 @@ pub struct Witness {
     pub iv:  Vec<u8>,
     pub ct:  Vec<u8>,
     pub pt:  Vec<u8>,
+    /// additional authenticated data, empty for modes without authentication
+    pub aad: Vec<u8>,
+    /// authentication tag, empty unless the mode produces it separately from `ct`
+    pub tag: Vec<u8>,
 }
 
 #[derive(Debug, Serialize)]
@@ -43,14 +52,59 @@ pub struct AesGcmSivInputs {
     pub CT:  Vec<u8>,
 }
 
+/// Signals of the `GHASH(NUM_BLOCKS)` circuit
+#[derive(Debug, Serialize)]
+pub struct GhashWitness {
+    pub HashKey: [u8; 16],
+    pub msg:     Vec<[u8; 16]>,
+    pub tag:     [u8; 16],
+}
+
+/// Signals of the `GhashMul()` circuit
+#[derive(Debug, Serialize)]
+pub struct GhashMulWitness {
+    pub X:   [u8; 16],
+    pub Y:   [u8; 16],
+    pub out: [u8; 16],
+}
+
+/// Signals of the `KeyExpansion()` circuit
+#[derive(Debug, Serialize)]
+pub struct KeyExpansionWitness {
+    pub key:         Vec<u8>,
+    pub keyExpanded: Vec<[u8; 4]>,
+}
+
+/// Signals of the `Cipher()` circuit, `block` and `cipher` are column-major states
+#[derive(Debug, Serialize)]
+pub struct CipherWitness {
+    pub block:  State,
+    pub key:    [u8; 16],
+    pub cipher: State,
+}
+
 impl Witness {
     pub fn new(key: &[u8], iv: &[u8], ct: &[u8], pt: &[u8]) -> Self {
-        Self { key: key.to_vec(), iv: iv.to_vec(), ct: ct.to_vec(), pt: pt.to_vec() }
+        Self {
+            key: key.to_vec(),
+            iv:  iv.to_vec(),
+            ct:  ct.to_vec(),
+            pt:  pt.to_vec(),
+            aad: vec![],
+            tag: vec![],
+        }
+    }
+
+    /// attach the aad and auth tag of an AEAD mode
+    pub fn with_aad_and_tag(mut self, aad: &[u8], tag: &[u8]) -> Self {
+        self.aad = aad.to_vec();
+        self.tag = tag.to_vec();
+        self
     }
 }
 
 /// AES cipher modes.
-#[derive(Default)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
 pub(crate) enum CipherMode {
     Vanilla, // no IV Here
     Ctr256,
@@ -62,126 +116,489 @@ pub(crate) enum CipherMode {
     GCM128,
 }
 
-/// borrowed from rust-tls
-fn encrypt_tls(message: &[u8], key: &[u8], iv: &[u8], seq: u64) -> Result<Vec<u8>> {
-    // see tls1.3; 1 byte type, 16b aad
-    let total_len = message.len() + 1 + 16;
+/// Seal a TLS 1.3 record, RFC 8446 section 5.2, as rustls does.
+///
+/// The plaintext is `TLSInnerPlaintext`: `message`, then the real `content_type`, then `padding`
+/// zero bytes. It is sealed under the per-record nonce `iv ^ seq` with the record header as the
+/// AAD. Returns the whole `TLSCiphertext`: the 5 byte header, then the ciphertext and tag.
+pub(crate) fn encrypt_tls(
+    message: &[u8],
+    content_type: u8,
+    padding: usize,
+    key: &[u8],
+    iv: &[u8],
+    seq: u64,
+) -> Result<Vec<u8>> {
+    ensure!(iv.len() == 12, "TLS 1.3 iv must be 12 bytes");
+    // see tls1.3; content, 1 byte type, zero padding, 16 byte tag
+    let total_len = message.len() + 1 + padding + 16;
+    ensure!(total_len <= (1 << 14) + 256, "TLSCiphertext is at most 2^14 + 256 bytes");
     let aad = make_tls13_aad(total_len);
-    let fixed_iv = iv[..12].try_into()?;
-    let nonce = make_nonce(fixed_iv, seq);
-
-    println!("ENC: msg={:?}, msg_len={:?}, seq={:?}", hex::encode(message), message.len(), seq);
-    println!("ENC: iv={:?}, dec_key={:?}", hex::encode(iv), hex::encode(key));
-    println!("ENC: nonce={:?}, aad={:?}", hex::encode(nonce), hex::encode(aad));
+    let nonce = make_nonce(iv.try_into()?, seq);
 
     let mut payload = Vec::with_capacity(total_len);
     payload.extend_from_slice(message);
-    // payload.push(0x17);  // Very important, encrypted messages must have the type appended.
+    payload.push(content_type);
+    payload.resize(payload.len() + padding, 0);
 
-    let aes_payload = Payload {
-        msg: &payload,
-        aad: &[], /* refactor remove
-                   * aad: &aad, */
-    };
+    let aes_payload = Payload { msg: &payload, aad: &aad };
+    let nonce = GenericArray::from_slice(&nonce);
+    let mode = if key.len() == 16 { CipherMode::GCM128 } else { CipherMode::GCM256 };
+    let ct = match key.len() {
+        16 => Aes128Gcm::new(GenericArray::from_slice(key)).encrypt(nonce, aes_payload),
+        32 => Aes256Gcm::new(GenericArray::from_slice(key)).encrypt(nonce, aes_payload),
+        _ => bail!("TLS 1.3 AES-GCM key must be 16 or 32 bytes"),
+    }
+    .map_err(|_| Error::Encryption(mode))?;
 
-    let cipher = Aes128Gcm::new_from_slice(key).unwrap();
-    let nonce = GenericArray::from_slice(iv);
-    Ok(cipher.encrypt(nonce, aes_payload).expect("error generating ct"))
+    Ok([aad.as_slice(), &ct].concat())
 }
 
-pub fn aes_witnesses(cipher_mode: CipherMode) -> Result<Witness> {
-    // Base ASCII versions using TLS encryption.
-    let ct = encrypt_tls(MESSAGE.as_bytes(), KEY_ASCII.as_bytes(), IV_ASCII.as_bytes(), 1).unwrap();
-    println!("ENC: cipher_text={:?}, cipher_len={:?}", hex::encode(ct.clone()), ct.len());
-
-    let key = GenericArray::from(KEY_BYTES_156);
-    let key_256 = GenericArray::from(KEY_BYTES_256);
-    let iv = GenericArray::from(IV_BYTES);
-    let mut block = GenericArray::from(MESSAGE_BYTES);
-    let mut block_256 = GenericArray::from(ZERO_MESSAGE_BYTES_256);
-
-    let ct = match cipher_mode {
-        CipherMode::Vanilla => {
-            let cipher = Aes128::new(&key);
-            cipher.encrypt_block(&mut block);
-            block.to_vec()
+/// Seal a TLS 1.2 AES-GCM record, RFC 5288. The explicit nonce is the sequence number, as most
+/// implementations send it. Returns the whole `TLSCiphertext`: the 5 byte header, the explicit
+/// nonce, then the ciphertext and tag.
+pub(crate) fn encrypt_tls12(
+    message: &[u8],
+    content_type: u8,
+    key: &[u8],
+    salt: &[u8],
+    seq: u64,
+) -> Result<Vec<u8>> {
+    ensure!(message.len() <= 1 << 14, "TLSPlaintext is at most 2^14 bytes");
+    let explicit_nonce = seq.to_be_bytes();
+    let nonce = make_tls12_nonce(salt.try_into()?, explicit_nonce);
+    let aad = make_tls12_aad(seq, content_type, message.len());
+
+    let aes_payload = Payload { msg: message, aad: &aad };
+    let nonce = GenericArray::from_slice(&nonce);
+    let mode = if key.len() == 16 { CipherMode::GCM128 } else { CipherMode::GCM256 };
+    let ct = match key.len() {
+        16 => Aes128Gcm::new(GenericArray::from_slice(key)).encrypt(nonce, aes_payload),
+        32 => Aes256Gcm::new(GenericArray::from_slice(key)).encrypt(nonce, aes_payload),
+        _ => bail!("TLS 1.2 AES-GCM key must be 16 or 32 bytes"),
+    }
+    .map_err(|_| Error::Encryption(mode))?;
+
+    let len = (explicit_nonce.len() + ct.len()) as u16;
+    let header = [content_type, 0x3, 0x3, (len >> 8) as u8, len as u8];
+    Ok([header.as_slice(), &explicit_nonce, &ct].concat())
+}
+
+/// AES-GCM assembled by hand, following NIST SP 800-38D section 7.1:
+/// - H = CIPH_K(0^128)
+/// - J0 = IV || 0^31 || 1 for a 96 bit IV, otherwise GHASH_H(IV || 0^(s+64) || [len(IV)]_64)
+/// - C = GCTR_K(inc32(J0), P)
+/// - T = GCTR_K(J0, GHASH_H(A || 0^v || C || 0^u || [len(A)]_64 || [len(C)]_64))
+///
+/// Takes a 16 or 32 byte key and an IV of any non-zero length. Returns the ciphertext and auth tag
+/// separately, as the `AESGCM(l)` circuit outputs them.
+pub(crate) fn encrypt_gcm(
+    key: &[u8],
+    iv: &[u8],
+    aad: &[u8],
+    pt: &[u8],
+) -> Result<(Vec<u8>, [u8; 16])> {
+    ensure!(!iv.is_empty(), "GCM IV must not be empty");
+    let cipher = block_cipher(key)?;
+
+    let mut hash_key = Block::default();
+    cipher(&mut hash_key);
+    let j0 = ghash_j0(&hash_key, iv);
+
+    // the first keystream block masks the tag, the rest encrypt the plaintext
+    let mut tag_mask = j0;
+    cipher(&mut tag_mask);
+    let mut counter = j0;
+    let mut ct = pt.to_vec();
+    for chunk in ct.chunks_mut(16) {
+        counter = inc32(counter);
+        let mut keystream = counter;
+        cipher(&mut keystream);
+        chunk.iter_mut().zip(keystream).for_each(|(c, k)| *c ^= k);
+    }
+
+    let mut len_block = ghash::Block::default();
+    len_block[..8].copy_from_slice(&(aad.len() as u64 * 8).to_be_bytes());
+    len_block[8..].copy_from_slice(&(ct.len() as u64 * 8).to_be_bytes());
+
+    let mut ghash = GHash::new(&hash_key);
+    ghash.update_padded(aad);
+    ghash.update_padded(&ct);
+    ghash.update(&[len_block]);
+    let s = ghash.finalize();
+
+    let mut tag = [0u8; 16];
+    tag.iter_mut().zip(s.iter().zip(tag_mask.iter())).for_each(|(t, (s, m))| *t = s ^ m);
+
+    Ok((ct, tag))
+}
+
+/// CIPH_K, encrypting a block in place
+type BlockCipherFn = Box<dyn Fn(&mut Block)>;
+
+/// CIPH_K for a 16 or 32 byte key
+fn block_cipher(key: &[u8]) -> Result<BlockCipherFn> {
+    Ok(match key.len() {
+        16 => {
+            let cipher = Aes128::new(GenericArray::from_slice(key));
+            Box::new(move |block| cipher.encrypt_block(block))
         },
-        CipherMode::Ctr256 => {
-            // AES CTR 256, adjusted to match GCM. ✅, matches AES-256-GCM impl
-            let mut cipher_256 = Aes256Ctr32BE::new(&key_256, &IV_BYTES_256.into());
-            let mut tag_mask_256 = Block::default();
-
-            cipher_256.apply_keystream(&mut tag_mask_256);
-            cipher_256.apply_keystream(&mut block_256);
-            block_256.to_vec()
+        32 => {
+            let cipher = Aes256::new(GenericArray::from_slice(key));
+            Box::new(move |block| cipher.encrypt_block(block))
         },
+        _ => bail!("AES-GCM key must be 16 or 32 bytes"),
+    })
+}
+
+/// The initial counter block J0 for `key` and an IV of any length, see [`encrypt_gcm`]
+pub fn gcm_j0(key: &[u8], iv: &[u8]) -> Result<Block> {
+    ensure!(!iv.is_empty(), "GCM IV must not be empty");
+    let mut hash_key = Block::default();
+    block_cipher(key)?(&mut hash_key);
+    Ok(ghash_j0(&hash_key, iv))
+}
+
+/// `GHASH(NUM_BLOCKS)` witness deriving J0 from an IV that is not 96 bits: the message is the IV
+/// zero padded to whole blocks, then the block `0^64 || [len(IV)]_64`, and the tag is J0.
+pub fn j0_ghash_witness(key: &[u8], iv: &[u8]) -> Result<GhashWitness> {
+    ensure!(!iv.is_empty(), "GCM IV must not be empty");
+    ensure!(iv.len() != 12, "a 96 bit IV gives J0 = IV || 0^31 || 1 without GHASH");
+    let mut hash_key = Block::default();
+    block_cipher(key)?(&mut hash_key);
+
+    let mut len_block = [0; 16];
+    len_block[8..].copy_from_slice(&(iv.len() as u64 * 8).to_be_bytes());
+    let msg = [pad_blocks(iv), vec![len_block]].concat();
+
+    Ok(ghash_witness(&hash_key.into(), &msg))
+}
+
+/// The GHASH input blocks of NIST SP 800-38D for an AAD of any length:
+/// A || 0^v || C || 0^u || [len(A)]_64 || [len(C)]_64
+pub(crate) fn gcm_ghash_message(aad: &[u8], ct: &[u8]) -> Vec<[u8; 16]> {
+    let mut len_block = [0; 16];
+    len_block[..8].copy_from_slice(&(aad.len() as u64 * 8).to_be_bytes());
+    len_block[8..].copy_from_slice(&(ct.len() as u64 * 8).to_be_bytes());
+    [pad_blocks(aad), pad_blocks(ct), vec![len_block]].concat()
+}
+
+/// Split into 16 byte blocks, zero padding the last
+fn pad_blocks(bytes: &[u8]) -> Vec<[u8; 16]> {
+    bytes
+        .chunks(16)
+        .map(|chunk| {
+            let mut block = [0; 16];
+            block[..chunk.len()].copy_from_slice(chunk);
+            block
+        })
+        .collect()
+}
+
+/// The GHASH input blocks built by the `AESGCM(l)` circuit: the AAD block, the ciphertext blocks
+/// and the length block.
+///
+/// This mirrors aes-gcm.circom rather than SP 800-38D, the two differ in a few places:
+/// - the AAD is written row by row into a column-major block, so it ends up transposed
+/// - a partial last ciphertext block is padded as `ToBlocks` does, `0x01` then zeros
+/// - `len(A)` is hardcoded to 128 bits and written to byte 13
+/// - `len(C)` counts whole blocks, and each of its bytes is reduced to its bit count
+pub(crate) fn aes_gcm_ghash_message(aad: &[u8; 16], ct: &[u8]) -> Vec<[u8; 16]> {
+    // ghashMessage[0][i][j] <== aad[i*4+j]
+    let aad_block: State = std::array::from_fn(|i| std::array::from_fn(|j| aad[i * 4 + j]));
+
+    let ct_blocks = to_blocks(ct);
+    let len = ct_blocks.len() as u64 * 128;
+
+    let mut len_block = State::default();
+    len_block[1] = [0x00, 0x00, 0x00, 0x80];
+    for i in 0..8 {
+        len_block[i / 4 + 2][i % 4] = ((len >> (i * 8)) as u8).count_ones() as u8;
+    }
+
+    std::iter::once(aad_block)
+        .chain(ct_blocks)
+        .chain(std::iter::once(len_block))
+        .map(|block| to_stream(&[block], 16).try_into().unwrap())
+        .collect()
+}
+
+/// `GHASH(NUM_BLOCKS)` witness: GHASH_H(X_1 || ... || X_m) for the hash key H
+pub fn ghash_witness(hash_key: &[u8; 16], msg: &[[u8; 16]]) -> GhashWitness {
+    let mut ghash = GHash::new(hash_key.into());
+    msg.iter().for_each(|block| ghash.update(&[(*block).into()]));
+    GhashWitness { HashKey: *hash_key, msg: msg.to_vec(), tag: ghash.finalize().into() }
+}
+
+/// `GhashMul()` witness: the product X • Y in GF(2^128). GHASH over a single block is Y • H, so
+/// hash `Y` with `X` as the key.
+pub fn ghash_mul_witness(x: &[u8; 16], y: &[u8; 16]) -> GhashMulWitness {
+    let out = ghash_witness(x, &[*y]).tag;
+    GhashMulWitness { X: *x, Y: *y, out }
+}
+
+/// `KeyExpansion()` witness: the key schedule as `4 * (Nr + 1)` words, `keyExpanded[i]` being w[i]
+/// of FIPS-197. A 16 byte key gives the 44 words the circuit outputs, a 32 byte key 60 words.
+pub fn key_expansion_witness(key: &[u8]) -> Result<KeyExpansionWitness> {
+    Ok(KeyExpansionWitness { key: key.to_vec(), keyExpanded: expand_key(key)? })
+}
+
+/// `Cipher()` witness: a single AES-128 block encryption
+pub fn cipher_witness(key: &[u8; 16], block: &[u8; 16]) -> CipherWitness {
+    let mut cipher = Block::clone_from_slice(block);
+    Aes128::new(key.into()).encrypt_block(&mut cipher);
+    CipherWitness { block: to_blocks(block)[0], key: *key, cipher: to_blocks(&cipher)[0] }
+}
+
+/// `GHASH(NUM_BLOCKS)` witness for the hash inside the `AESGCM(l)` circuit: H = CIPH_K(0^128)
+/// over the message from [`aes_gcm_ghash_message`].
+pub fn aes_gcm_ghash_witness(witness: &Witness) -> Result<GhashWitness> {
+    ensure!(witness.key.len() == 16, "AESGCM key must be 16 bytes");
+    ensure!(witness.aad.len() == 16, "AESGCM aad must be 16 bytes");
+    ensure_gctr_len(witness.ct.len(), &inc32(gcm_j0(&witness.key, &witness.iv)?))?;
+
+    let mut hash_key = Block::default();
+    Aes128::new(Block::from_slice(&witness.key)).encrypt_block(&mut hash_key);
+
+    let msg = aes_gcm_ghash_message(witness.aad[..].try_into()?, &witness.ct);
+    Ok(ghash_witness(&hash_key.into(), &msg))
+}
+
+/// `GHASH(NUM_BLOCKS)` witness for the spec hash S of a GCM witness with an AAD of any length, over
+/// the message from [`gcm_ghash_message`]. The tag is then T = S ^ CIPH_K(J0).
+pub fn gcm_ghash_witness(witness: &Witness) -> Result<GhashWitness> {
+    let mut hash_key = Block::default();
+    block_cipher(&witness.key)?(&mut hash_key);
+
+    let msg = gcm_ghash_message(&witness.aad, &witness.ct);
+    Ok(ghash_witness(&hash_key.into(), &msg))
+}
+
+/// Builds a [`Witness`] for a [`CipherMode`] from caller supplied key, IV, plaintext and AAD.
+///
+/// ```ignore
+/// let witness = WitnessBuilder::new(CipherMode::GCM128)
+///     .key(&[0; 16])
+///     .iv(&[0; 12])
+///     .pt(b"hello world")
+///     .aad(&[0; 16])
+///     .build()?;
+/// ```
+#[derive(Debug, Default)]
+pub struct WitnessBuilder {
+    mode: CipherMode,
+    key:  Vec<u8>,
+    iv:   Vec<u8>,
+    pt:   Vec<u8>,
+    aad:  Vec<u8>,
+}
+
+impl WitnessBuilder {
+    pub fn new(mode: CipherMode) -> Self { Self { mode, ..Default::default() } }
+
+    /// 16 byte key for the 128 bit modes, 32 byte key for the 256 bit modes
+    pub fn key(mut self, key: &[u8]) -> Self {
+        self.key = key.to_vec();
+        self
+    }
+
+    /// IV (nonce) of any non-zero length for the CTR and GCM modes, 12 bytes for `GcmSiv`,
+    /// unused by `Vanilla`
+    pub fn iv(mut self, iv: &[u8]) -> Self {
+        self.iv = iv.to_vec();
+        self
+    }
+
+    /// any length for the CTR and GCM modes, a single 16 byte block for `Vanilla`
+    pub fn pt(mut self, pt: &[u8]) -> Self {
+        self.pt = pt.to_vec();
+        self
+    }
+
+    /// additional authenticated data, only used by the AEAD modes
+    pub fn aad(mut self, aad: &[u8]) -> Self {
+        self.aad = aad.to_vec();
+        self
+    }
+
+    /// Encrypt the inputs. The returned witness carries exactly the key, IV and AAD used.
+    pub fn build(self) -> Result<Witness> {
+        let Self { mode, key, iv, pt, aad } = self;
+
+        let key_len = match mode {
+            CipherMode::Vanilla | CipherMode::Ctr128 | CipherMode::GCM128 => 16,
+            CipherMode::Ctr256 | CipherMode::GcmSiv | CipherMode::GCM256 => 32,
+        };
+        ensure!(
+            key.len() == key_len,
+            "{:?} key must be {} bytes, got {}",
+            mode,
+            key_len,
+            key.len()
+        );
+        match mode {
+            CipherMode::Vanilla => {},
+            CipherMode::GcmSiv =>
+                ensure!(iv.len() == 12, "{:?} IV must be 12 bytes, got {}", mode, iv.len()),
+            // GCM and the CTR modes matching it take any IV length, through J0
+            _ => ensure!(!iv.is_empty(), "{:?} IV must not be empty", mode),
+        }
+
+        let witness = match mode {
+            CipherMode::Vanilla => {
+                ensure!(pt.len() == 16, "Vanilla encrypts a single 16 byte block");
+                let cipher = Aes128::new(GenericArray::from_slice(&key));
+                let mut block = Block::clone_from_slice(&pt);
+                cipher.encrypt_block(&mut block);
+                Witness::new(&key, &[], &block, &pt)
+            },
+            CipherMode::Ctr256 => {
+                // AES CTR 256, adjusted to match GCM. ✅, matches AES-256-GCM impl
+                let mut cipher =
+                    Aes256Ctr32BE::new(GenericArray::from_slice(&key), &gcm_j0(&key, &iv)?);
+                let mut tag_mask = Block::default();
+                cipher.apply_keystream(&mut tag_mask);
+                let mut ct = pt.clone();
+                cipher.apply_keystream(&mut ct);
+                Witness::new(&key, &iv, &ct, &pt)
+            },
+            CipherMode::GcmSiv => {
+                // AES GCM SIV, WOO MATCHES CIRCOM!! ✅
+                // The circuit takes the tag appended to the ciphertext as `CT`, so keep them
+                // joined.
+                use aes_gcm_siv::{
+                    aead::{Aead, Payload as SIVPayload},
+                    Aes256GcmSiv,
+                };
+                let cipher = Aes256GcmSiv::new(GenericArray::from_slice(&key));
+                let nonce = GenericArray::from_slice(&iv);
+                let aes_payload = SIVPayload { msg: &pt, aad: &aad };
+                let ct = cipher.encrypt(nonce, aes_payload).map_err(|_| Error::Encryption(mode))?;
+                Witness::new(&key, &iv, &ct, &pt).with_aad_and_tag(&aad, &[])
+            },
+            CipherMode::Ctr128 => {
+                // AES CTR 128, adjusted to match GCM. ✅, matches AES-128-GCM impl
+                let mut cipher =
+                    Aes128Ctr32BE::new(GenericArray::from_slice(&key), &gcm_j0(&key, &iv)?);
+                let mut tag_mask = Block::default();
+                cipher.apply_keystream(&mut tag_mask); // In AES-GCM, an empty mask is encrypted first.
+                let mut ct = pt.clone();
+                cipher.apply_keystream(&mut ct);
+                Witness::new(&key, &iv, &ct, &pt)
+            },
+            CipherMode::GCM128 | CipherMode::GCM256 => {
+                // AES GCM, GCM128 being the mode implemented by the `AESGCM(l)` circuit
+                let (ct, tag) = encrypt_gcm(&key, &iv, &aad, &pt)?;
+
+                // cross-check against rust crypto, which appends the tag to the ciphertext and
+                // only takes 96 bit IVs
+                if iv.len() == 12 {
+                    let nonce = GenericArray::from_slice(&iv);
+                    let aes_payload = Payload { msg: &pt, aad: &aad };
+                    let ct_and_tag = match mode {
+                        CipherMode::GCM128 => Aes128Gcm::new(GenericArray::from_slice(&key))
+                            .encrypt(nonce, aes_payload),
+                        _ => Aes256Gcm::new(GenericArray::from_slice(&key))
+                            .encrypt(nonce, aes_payload),
+                    }
+                    .map_err(|_| Error::Encryption(mode))?;
+                    check_ct_and_tag(&ct_and_tag, &ct, &tag)?;
+                }
+
+                Witness::new(&key, &iv, &ct, &pt).with_aad_and_tag(&aad, &tag)
+            },
+        };
+
+        Ok(witness)
+    }
+}
+
+/// Compare a hand assembled ciphertext and tag against rust crypto's, which appends the tag
+fn check_ct_and_tag(ct_and_tag: &[u8], ct: &[u8], tag: &[u8]) -> Result<()> {
+    let (expected_ct, expected_tag) = ct_and_tag.split_at(ct.len());
+    for (signal, expected, actual) in [("ct", expected_ct, ct), ("tag", expected_tag, tag)] {
+        if expected != actual {
+            return Err(Error::OutputMismatch {
+                signal:   signal.to_string(),
+                expected: expected.to_vec(),
+                actual:   actual.to_vec(),
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Decrypt captured ciphertext, `ct` with its tag appended, and verify the tag.
+///
+/// Supports the AEAD modes: `GCM128` and `GCM256` return the witness with ciphertext and tag split
+/// as [`WitnessBuilder`] does. `GcmSiv` keeps them joined, the layout the AES-GCM-SIV circuit
+/// decrypts and checks with its success bit. Fails with [`Error::TagMismatch`] if the tag does not
+/// verify.
+///
+/// Only takes a 96 bit IV, the one length rust crypto decrypts; GCM witnesses for other IV lengths
+/// come from [`WitnessBuilder`] instead.
+pub fn decrypt_witness(
+    mode: CipherMode,
+    key: &[u8],
+    iv: &[u8],
+    aad: &[u8],
+    ct: &[u8],
+) -> Result<Witness> {
+    ensure!(ct.len() >= 16, "ciphertext must end in a 16 byte tag");
+    ensure!(iv.len() == 12, "{:?} IV must be 12 bytes, got {}", mode, iv.len());
+    let key_len = match mode {
+        CipherMode::GCM128 => 16,
+        CipherMode::GCM256 | CipherMode::GcmSiv => 32,
+        _ => bail!("{:?} has no authentication tag to verify", mode),
+    };
+    ensure!(key.len() == key_len, "{:?} key must be {} bytes, got {}", mode, key_len, key.len());
+
+    let nonce = GenericArray::from_slice(iv);
+    let aes_payload = Payload { msg: ct, aad };
+    let pt = match mode {
+        CipherMode::GCM128 =>
+            Aes128Gcm::new(GenericArray::from_slice(key)).decrypt(nonce, aes_payload),
+        CipherMode::GCM256 =>
+            Aes256Gcm::new(GenericArray::from_slice(key)).decrypt(nonce, aes_payload),
         CipherMode::GcmSiv => {
-            // AES GCM SIV, WOO MATCHES CIRCOM!! ✅
             use aes_gcm_siv::{
                 aead::{Aead, Payload as SIVPayload},
                 Aes256GcmSiv,
             };
-            let cipher = Aes256GcmSiv::new_from_slice(&key_256).unwrap();
-            let nonce = GenericArray::from_slice(&IV_BYTES_SHORT_256);
-            let aes_payload = SIVPayload { msg: &ZERO_MESSAGE_BYTES_256, aad: &SIV_AAD };
-            let ciphertext_siv = cipher.encrypt(nonce, aes_payload).expect("error generating ct");
-            println!(
-                "AES GCM 256 SIV: ct={:?}, bytes={:?}",
-                hex::encode(ciphertext_siv.clone()),
-                ciphertext_siv
-            );
-            ciphertext_siv.to_vec()
-        },
-        CipherMode::GCM256 => {
-            // Standard AES 256 GCM
-            let cipher = Aes256Gcm::new_from_slice(&key_256).unwrap();
-            let nonce = GenericArray::from_slice(&IV_BYTES_SHORT_256);
-            let aes_payload = Payload { msg: &ZERO_MESSAGE_BYTES_256, aad: &SIV_AAD };
-            let ct = cipher.encrypt(nonce, aes_payload).expect("error generating ct");
-            ct.to_vec()
-        },
-        CipherMode::Ctr128 => {
-            // AES CTR 128, adjusted to match GCM. ✅, matches AES-128-GCM impl
-            let mut cipher = Aes128Ctr32BE::new(&key, &iv);
-            let mut tag_mask = Block::default();
-            cipher.apply_keystream(&mut tag_mask); // In AES-GCM, an empty mask is encrypted first.
-            cipher.apply_keystream(&mut block);
-            block.to_vec()
-        },
-        CipherMode::GCM128 => {
-            unimplemented!()
+            Aes256GcmSiv::new(GenericArray::from_slice(key))
+                .decrypt(nonce, SIVPayload { msg: ct, aad })
+                .map_err(|_| aes_gcm::Error)
         },
-    };
+        _ => bail!("{:?} has no authentication tag to verify", mode),
+    }
+    .map_err(|_| Error::TagMismatch(mode))?;
 
-    // more manual AESGCM using rust crypto should be equiv to output of encrypt tls
-    // can remove or assert equiv later
-    //
-    // AES-GCM Duplication. NOTE: This is identical to section 246.
-    // Init logic in AES-GCM. This standard procedure can be applied to the TLS IV.
-    let mut ghash_iv = ghash::Block::default();
-    ghash_iv[..12].copy_from_slice(&IV_BYTES_SHORT);
-    ghash_iv[15] = 1;
-
-    let inner_cipher = Aes128::new(&key);
-    let mut ctr = Ctr32BE::inner_iv_init(&inner_cipher, &ghash_iv);
-    let mut tag_mask = Block::default();
-
-    ctr.write_keystream_block(&mut tag_mask);
-    let mut buffer: Vec<u8> = Vec::new();
-    buffer.extend_from_slice(MESSAGE.as_bytes());
-    apply_keystream(ctr, &mut buffer);
-
-    // WORKING! The aes-ctr and aes-gcm now match.
-    println!("INPUT iv={:?}, key={:?}", hex::encode(IV_BYTES), hex::encode(KEY_BYTES_156));
-    println!(
-        "AES GCM IV={:?}, tm={:?}, ct={:?}",
-        hex::encode(ghash_iv),
-        hex::encode(tag_mask),
-        hex::encode(buffer)
+    // re-encrypt, so the witness is exactly what the builder makes for this plaintext
+    let witness = WitnessBuilder::new(mode).key(key).iv(iv).aad(aad).pt(&pt).build()?;
+    ensure!(
+        [witness.ct.as_slice(), &witness.tag].concat() == ct,
+        "{:?} re-encryption does not reproduce the ciphertext",
+        mode
     );
-    println!("AES CTR: ct={:?}", hex::encode(block));
-    println!("AES CTR 256, 96 IV: ct={:?}", hex::encode(block));
-    println!("AES GCM 256: ct={:?}", hex::encode(ct.clone()));
+    Ok(witness)
+}
+
+/// Witnesses for the fixed test vectors in `consts`
+pub fn aes_witnesses(cipher_mode: CipherMode) -> Result<Witness> {
+    let builder = WitnessBuilder::new(cipher_mode);
+    let builder = match cipher_mode {
+        CipherMode::Vanilla => builder.key(&KEY_BYTES_156).pt(&MESSAGE_BYTES),
+        CipherMode::Ctr256 =>
+            builder.key(&KEY_BYTES_256).iv(&IV_BYTES_SHORT_256).pt(&ZERO_MESSAGE_BYTES_256),
+        CipherMode::GcmSiv | CipherMode::GCM256 => builder
+            .key(&KEY_BYTES_256)
+            .iv(&IV_BYTES_SHORT_256)
+            .pt(&ZERO_MESSAGE_BYTES_256)
+            .aad(&SIV_AAD),
+        CipherMode::Ctr128 => builder.key(&KEY_BYTES_156).iv(&IV_BYTES_SHORT).pt(&MESSAGE_BYTES),
+        CipherMode::GCM128 =>
+            builder.key(&KEY_BYTES_156).iv(&IV_BYTES_SHORT).pt(&MESSAGE_BYTES).aad(&GCM_AAD),
+    };
 
-    Ok(Witness::new(&KEY_BYTES_156, &IV_BYTES_SHORT_256, &ct, &ZERO_MESSAGE_BYTES_256))
+    builder.build()
 }